@@ -1,6 +1,7 @@
 use serde::de::{self, Visitor};
 
 use super::{Deserializer, Error, Result};
+use crate::value::VALUE_NAME;
 
 pub struct IdDeserializer<'a, 'b: 'a> {
     d: &'a mut Deserializer<'b>,
@@ -176,10 +177,15 @@ impl<'a, 'b: 'a, 'c> de::Deserializer<'b> for &'c mut IdDeserializer<'a, 'b> {
         unimplemented!("IdDeserializer may only be used for identifiers")
     }
 
-    fn deserialize_newtype_struct<V>(self, _: &'static str, _: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'b>,
     {
+        // A `Value` deserializes as a newtype struct to recognize date/time literals
+        if name == VALUE_NAME {
+            return self.deserialize_any(visitor);
+        }
+
         unimplemented!("IdDeserializer may only be used for identifiers")
     }
 
@@ -0,0 +1,173 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use super::{Error, ErrorCode, Result};
+use crate::extensions::Extensions;
+use crate::parse::{Bytes, FileId};
+
+/// Loads the files named by `include!` directives.
+pub trait Resolver {
+    /// Returns a name and the content of the file at `path`, included from
+    /// the file named `from`, or from the document itself if `from` is `None`.
+    ///
+    /// The name has to be the same whenever the file is included, since it
+    /// identifies the file in error positions and when detecting cycles.
+    fn resolve(&mut self, from: Option<&str>, path: &str) -> io::Result<(String, String)>;
+}
+
+/// Resolves paths relative to the directory of the including file, and
+/// paths included from the document itself relative to a base directory.
+#[derive(Clone, Debug)]
+pub struct FileResolver {
+    base: PathBuf,
+}
+
+impl FileResolver {
+    pub fn new<P: Into<PathBuf>>(base: P) -> Self {
+        FileResolver { base: base.into() }
+    }
+}
+
+impl Resolver for FileResolver {
+    fn resolve(&mut self, from: Option<&str>, path: &str) -> io::Result<(String, String)> {
+        let dir = match from {
+            Some(from) => Path::new(from).parent().unwrap_or_else(|| Path::new("")),
+            None => &self.base,
+        };
+        let path = normalize(&dir.join(path));
+        let content = fs::read_to_string(&path)?;
+
+        Ok((path.to_string_lossy().into_owned(), content))
+    }
+}
+
+/// Removes `.` components and `..` components following a directory, so
+/// each file gets the same name however it is reached.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(normal.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                normal.pop();
+            }
+            component => normal.push(component),
+        }
+    }
+
+    normal
+}
+
+/// The files included by a document, which are loaded before
+/// deserializing it, see `Deserializer::with_includes`.
+#[derive(Clone, Debug, Default)]
+pub struct Includes {
+    /// The name and content of each file, indexed by its `FileId`.
+    files: Vec<(String, String)>,
+    /// The file each directive refers to, by the including file and the
+    /// path written in the directive.
+    targets: HashMap<(Option<FileId>, String), FileId>,
+}
+
+impl Includes {
+    /// Loads the files included by `input` through `resolver`, and the
+    /// files they include in turn.
+    pub fn load<R: Resolver>(input: &str, resolver: &mut R) -> Result<Self> {
+        let mut includes = Includes::default();
+        let bytes = Bytes::new(input.as_bytes())?;
+
+        includes.load_file(bytes, resolver, &mut Vec::new())?;
+
+        Ok(includes)
+    }
+
+    fn load_file<R: Resolver>(
+        &mut self,
+        bytes: Bytes<'_>,
+        resolver: &mut R,
+        stack: &mut Vec<FileId>,
+    ) -> Result<()> {
+        if !bytes.exts.contains(Extensions::INCLUDE) {
+            return Ok(());
+        }
+
+        let from = bytes.file();
+
+        for (path, position) in bytes.includes()? {
+            let key = (from, path);
+            if self.targets.contains_key(&key) {
+                continue;
+            }
+
+            let (name, content) = resolver
+                .resolve(from.and_then(|from| self.file_name(from)), &key.1)
+                .map_err(|e| {
+                    position.error(ErrorCode::IncludeFailed {
+                        path: key.1.clone(),
+                        error: e.to_string(),
+                    })
+                })?;
+
+            if let Some(index) = self.files.iter().position(|(file, _)| *file == name) {
+                if stack.contains(&FileId(index)) {
+                    return position.err(ErrorCode::IncludeCycle(name));
+                }
+                self.targets.insert(key, FileId(index));
+                continue;
+            }
+
+            // The content is stored once the files it includes are loaded,
+            // but its id is needed to locate errors and resolve paths.
+            let file = FileId(self.files.len());
+            self.files.push((name, String::new()));
+            self.targets.insert(key, file);
+
+            let mut included = Bytes::with_file(content.as_bytes(), Some(file))?;
+            included.exts |= bytes.exts;
+
+            stack.push(file);
+            self.load_file(included, resolver, stack)?;
+            stack.pop();
+
+            self.files[file.0].1 = content;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the name of the included file `file`, as given by the
+    /// `Resolver` which loaded it.
+    pub fn file_name(&self, file: FileId) -> Option<&str> {
+        self.files.get(file.0).map(|(name, _)| name.as_str())
+    }
+
+    /// Formats `error` like its `Display` implementation, but naming the
+    /// included file it is located in, as in `enemies.ron:2:9: Expected integer`.
+    pub fn describe(&self, error: &Error) -> String {
+        let position = error.position;
+
+        match position.file.and_then(|file| self.file_name(file)) {
+            Some(name) => format!(
+                "{}:{}:{}: {}",
+                name, position.line, position.col, error.code
+            ),
+            None => error.to_string(),
+        }
+    }
+
+    /// Returns the id and content of the file included with `path` from
+    /// the file `from`.
+    pub(crate) fn get(&self, from: Option<FileId>, path: &str) -> Option<(FileId, &str)> {
+        let file = *self.targets.get(&(from, path.to_owned()))?;
+
+        self.files
+            .get(file.0)
+            .map(|(_, content)| (file, content.as_str()))
+    }
+}
@@ -1,27 +1,139 @@
 /// Deserialization module.
 pub use crate::error::{Error, ErrorCode, Result};
-pub use crate::parse::Position;
+pub use crate::parse::{FileId, Position};
 
+use serde::de::value::BorrowedStrDeserializer;
 use serde::de::{self, DeserializeSeed, Deserializer as SerdeError, Visitor};
-use std::{borrow::Cow, io, str};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    io, mem, str,
+};
 
 use self::id::IdDeserializer;
+use self::key::MapKeyDeserializer;
 use self::tag::TagDeserializer;
 use crate::extensions::Extensions;
-use crate::parse::{AnyNum, Bytes, ParsedStr};
+use crate::parse::{self, AnyNum, Bytes, ParsedStr};
+#[cfg(feature = "datetime")]
+use crate::value::DATETIME_KEY;
+use crate::value::{Value, SET_KEY, VALUE_NAME};
+
+pub use self::include::{FileResolver, Includes, Resolver};
 
 mod id;
+mod include;
+mod key;
 mod tag;
 #[cfg(test)]
 mod tests;
 mod value;
 
+/// The most aliases replayed while deserializing a document, so a few
+/// anchors referencing each other cannot expand to an exponential size.
+const MAX_ALIAS_REPLAYS: usize = 10_000;
+/// The most aliases replayed within each other.
+const MAX_ALIAS_DEPTH: usize = 64;
+/// The most elements produced by `[expr; N]` repetitions while
+/// deserializing a document, including those of nested repetitions.
+const MAX_REPEATED_ELEMENTS: usize = 1_000_000;
+/// The most included files deserialized for a document, so files which
+/// include each other several times cannot expand to an exponential size.
+const MAX_INCLUDE_REPLAYS: usize = 10_000;
+
+/// Replays an alias or an included file at the current position and
+/// records an anchored value, returning early in these cases.
+macro_rules! redirected {
+    ($self:ident, $visitor:ident, |$de:ident| $deserialize:expr) => {
+        let $visitor = match $self.anchored($visitor, |$de, $visitor| $deserialize)? {
+            Ok(value) => return Ok(value),
+            Err(visitor) => visitor,
+        };
+        let $visitor = match $self.included($visitor, |$de, $visitor| $deserialize)? {
+            Ok(value) => return Ok(value),
+            Err(visitor) => visitor,
+        };
+    };
+}
+
 /// The RON deserializer.
 ///
 /// If you just want to simply deserialize a value,
 /// you can use the `from_str` convenience function.
 pub struct Deserializer<'de> {
     bytes: Bytes<'de>,
+    input: &'de [u8],
+    comments: Option<Comments<'de>>,
+    /// Remaining input length at the start of a newtype variant's value,
+    /// whose parentheses a struct or tuple shares if
+    /// `unwrap_variant_newtypes` is enabled
+    newtype_variant: Option<usize>,
+    /// Remaining input length at the start of the document, where a struct
+    /// is read without parentheses if `implicit_outer_struct` is enabled
+    outer_struct: Option<usize>,
+    anchors: Anchors<'de>,
+    /// The files `include!` directives refer to
+    includes: Option<&'de Includes>,
+    /// The struct and variant names read by `deserialize_any`, which are
+    /// recorded to compare the elements of a set literal since `Value`
+    /// does not keep them
+    names: Option<Vec<&'de [u8]>>,
+}
+
+/// An element of a set literal as a `Value` and its struct and variant
+/// names, which is compared with the other elements.
+type SetElement<'de> = (Value, Vec<&'de [u8]>);
+
+/// Anchored values seen while deserializing, and the counts which limit
+/// how often values are deserialized again.
+#[derive(Clone, Default)]
+struct Anchors<'de> {
+    /// The source of each anchored value by anchor name.
+    values: HashMap<&'de [u8], Bytes<'de>>,
+    /// The number of aliases replayed so far.
+    replays: usize,
+    /// The number of aliases currently being replayed.
+    depth: usize,
+    /// The number of elements produced by repetitions so far.
+    repeated: usize,
+    /// The number of included files deserialized so far.
+    included: usize,
+}
+
+/// Comments collected while deserializing.
+struct Comments<'de> {
+    table: HashMap<String, String>,
+    path: Vec<String>,
+    /// The input which has not been searched for comments yet.
+    unscanned: &'de [u8],
+    /// The path of the last value, which owns comments on the same line.
+    previous: Option<String>,
+}
+
+impl<'de> Comments<'de> {
+    /// Records the comments skipped between `unscanned` and `until`.
+    fn scan(&mut self, until: &'de [u8], exts: Extensions) {
+        let gap = &self.unscanned[..self.unscanned.len() - until.len()];
+
+        for (own_line, comment) in parse::comments(gap, exts) {
+            let owner = match self.previous {
+                Some(ref previous) if !own_line => previous.clone(),
+                _ => self.path.join("."),
+            };
+
+            self.table
+                .entry(owner)
+                .and_modify(|c| {
+                    c.push('\n');
+                    c.push_str(&comment);
+                })
+                .or_insert(comment);
+        }
+
+        self.unscanned = until;
+        self.previous = None;
+    }
 }
 
 impl<'de> Deserializer<'de> {
@@ -32,13 +144,62 @@ impl<'de> Deserializer<'de> {
     }
 
     pub fn from_bytes(input: &'de [u8]) -> Result<Self> {
+        let bytes = Bytes::new(input)?;
+        let outer_struct = if bytes.exts.contains(Extensions::IMPLICIT_OUTER_STRUCT) {
+            Some(bytes.bytes().len())
+        } else {
+            None
+        };
+
         Ok(Deserializer {
-            bytes: Bytes::new(input)?,
+            bytes,
+            input,
+            comments: None,
+            newtype_variant: None,
+            outer_struct,
+            anchors: Anchors::default(),
+            includes: None,
+            names: None,
         })
     }
 
+    /// Deserializes `include!` directives from the files in `includes`,
+    /// which have to be loaded from the same document first.
+    pub fn with_includes(mut self, includes: &'de Includes) -> Self {
+        self.includes = Some(includes);
+
+        self
+    }
+
+    /// Collects the comments of the document while deserializing,
+    /// which can be retrieved with `take_comments` afterwards.
+    ///
+    /// Comments are keyed by the path of the field, element or map entry
+    /// they belong to, using the same format as `PrettyConfig::comments`.
+    /// Map keys are used as written in the document.
+    /// A comment belongs to the value following it, except for comments
+    /// on the same line after a value, which belong to that value.
+    pub fn with_comments(mut self) -> Self {
+        self.comments = Some(Comments {
+            table: HashMap::new(),
+            path: Vec::new(),
+            unscanned: self.input,
+            previous: None,
+        });
+
+        self
+    }
+
+    /// Returns the comments collected so far, see `with_comments`.
+    pub fn take_comments(&mut self) -> HashMap<String, String> {
+        self.comments
+            .as_mut()
+            .map(|c| std::mem::take(&mut c.table))
+            .unwrap_or_default()
+    }
+
     pub fn remainder(&self) -> Cow<'_, str> {
-        String::from_utf8_lossy(&self.bytes.bytes())
+        String::from_utf8_lossy(self.bytes.bytes())
     }
 }
 
@@ -78,11 +239,44 @@ where
     Ok(t)
 }
 
+/// A convenience function for deserializing a value of type `T` from a string
+/// whose `include!` directives refer to the files in `includes`.
+///
+/// See `Includes::load` for loading the included files.
+pub fn from_str_with_includes<'a, T>(s: &'a str, includes: &'a Includes) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_str(s)?.with_includes(includes);
+    let t = T::deserialize(&mut deserializer)?;
+
+    deserializer.end()?;
+
+    Ok(t)
+}
+
+/// A convenience function for deserializing a value of type `T` from a string,
+/// which also returns the comments of the document keyed by their path.
+///
+/// See `Deserializer::with_comments` for how comments are assigned to paths.
+pub fn from_str_with_comments<'a, T>(s: &'a str) -> Result<(T, HashMap<String, String>)>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_str(s)?.with_comments();
+    let t = T::deserialize(&mut deserializer)?;
+
+    deserializer.end()?;
+
+    Ok((t, deserializer.take_comments()))
+}
+
 impl<'de> Deserializer<'de> {
     /// Check if the remaining bytes are whitespace only,
     /// otherwise return an error.
     pub fn end(&mut self) -> Result<()> {
         self.bytes.skip_ws()?;
+        self.scan_comments();
 
         if self.bytes.bytes().is_empty() {
             Ok(())
@@ -91,6 +285,376 @@ impl<'de> Deserializer<'de> {
         }
     }
 
+    /// Records the comments skipped since the last scan.
+    fn scan_comments(&mut self) {
+        if let Some(ref mut comments) = self.comments {
+            comments.scan(self.bytes.bytes(), self.bytes.exts);
+        }
+    }
+
+    /// Descends into `segment` of the comment path, recording the comments
+    /// skipped before `start` where the field, element or map entry begins.
+    fn enter_comment_path(&mut self, start: &'de [u8], segment: String) {
+        if let Some(ref mut comments) = self.comments {
+            comments.path.push(segment);
+            comments.scan(start, self.bytes.exts);
+        }
+    }
+
+    /// Skips the input parsed since the last scan, which contains no comments.
+    fn skip_comment_scan(&mut self) {
+        if let Some(ref mut comments) = self.comments {
+            comments.unscanned = self.bytes.bytes();
+        }
+    }
+
+    /// Leaves the current comment path after its value has been parsed.
+    fn leave_comment_path(&mut self) {
+        if let Some(ref mut comments) = self.comments {
+            comments.previous = Some(comments.path.join("."));
+            comments.path.pop();
+            comments.unscanned = self.bytes.bytes();
+        }
+    }
+
+    /// Returns the source text of the map key or field name starting
+    /// at the current position, without consuming it.
+    fn key_source(&mut self, is_field: bool) -> Result<&'de str> {
+        let start = self.bytes.bytes();
+        let mut key = Deserializer {
+            bytes: self.bytes,
+            input: self.input,
+            comments: None,
+            newtype_variant: None,
+            outer_struct: None,
+            // Aliases replayed here count towards the limits as well
+            anchors: mem::take(&mut self.anchors),
+            includes: self.includes,
+            names: None,
+        };
+
+        let source = if is_field {
+            key.bytes.identifier()
+        } else {
+            match key.unquoted_key() {
+                Ok(Some(_)) => {
+                    let source = &start[..start.len() - key.bytes.bytes().len()];
+
+                    Ok(source.strip_prefix(b"r#").unwrap_or(source))
+                }
+                Ok(None) => <de::IgnoredAny as de::Deserialize>::deserialize(&mut key)
+                    .map(|_| &start[..start.len() - key.bytes.bytes().len()]),
+                Err(e) => Err(e),
+            }
+        };
+        self.anchors = key.anchors;
+        let source = source?;
+
+        str::from_utf8(source)
+            .map(str::trim_end)
+            .map_err(|e| self.bytes.error(e.into()))
+    }
+
+    /// Returns `true` at the start of a newtype variant's value which is
+    /// written without its own parentheses.
+    fn is_newtype_variant_start(&self) -> bool {
+        self.newtype_variant == Some(self.bytes.bytes().len())
+    }
+
+    /// Returns `true` at the start of the document if it consists of the
+    /// fields of a struct without parentheses.
+    fn is_outer_struct_start(&self) -> Result<bool> {
+        if self.outer_struct != Some(self.bytes.bytes().len()) {
+            return Ok(false);
+        }
+
+        let mut bytes = self.bytes;
+        if bytes.peek().is_none() {
+            return Ok(true);
+        }
+        if bytes.identifier().is_err() {
+            return Ok(false);
+        }
+        bytes.skip_ws()?;
+
+        Ok(bytes.consume(":"))
+    }
+
+    /// Deserializes the element of a set literal at `start` again, as a
+    /// `Value` together with its struct and variant names, so elements
+    /// can be compared without their type.
+    fn set_element(&mut self, start: Bytes<'de>) -> Result<SetElement<'de>> {
+        let mut element = Deserializer {
+            bytes: start,
+            input: self.input,
+            comments: None,
+            newtype_variant: None,
+            outer_struct: None,
+            anchors: mem::take(&mut self.anchors),
+            includes: self.includes,
+            names: Some(Vec::new()),
+        };
+
+        let value = <Value as de::Deserialize>::deserialize(&mut element);
+        self.anchors = element.anchors;
+
+        Ok((value?, element.names.unwrap_or_default()))
+    }
+
+    /// Parses the `expr; N` of a repetition following a `[`, if
+    /// `array_repetition` is enabled and a `;` follows the first element.
+    fn repetition(&mut self) -> Result<Option<Repetition<'de>>> {
+        if !self.bytes.exts.contains(Extensions::ARRAY_REPETITION) {
+            return Ok(None);
+        }
+
+        self.bytes.skip_ws()?;
+        let len = match self.bytes.repetition_len()? {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+
+        let element = self.bytes;
+        let mut rest = self.bytes;
+        rest.advance(len + 1)?;
+        rest.skip_ws()?;
+
+        let count = rest.unsigned_integer::<u64>()?;
+        let count = usize::try_from(count)
+            .ok()
+            .filter(|count| *count <= MAX_REPEATED_ELEMENTS - self.anchors.repeated)
+            .ok_or_else(|| rest.error(ErrorCode::RepetitionLimitExceeded))?;
+        self.anchors.repeated += count;
+
+        rest.skip_ws()?;
+
+        // The element is only parsed when it is repeated, so an element
+        // repeated zero times is checked here
+        if count == 0 {
+            let mut element = Deserializer {
+                bytes: element,
+                input: self.input,
+                comments: None,
+                newtype_variant: None,
+                outer_struct: None,
+                anchors: mem::take(&mut self.anchors),
+                includes: self.includes,
+                names: None,
+            };
+            let ignored = <de::IgnoredAny as de::Deserialize>::deserialize(&mut element);
+            self.anchors = element.anchors;
+            ignored?;
+
+            element.bytes.skip_ws()?;
+            if !element.bytes.consume(";") {
+                return element.bytes.err(ErrorCode::ExpectedComma);
+            }
+        }
+
+        self.bytes = rest;
+
+        Ok(Some(Repetition {
+            element,
+            input: self.input,
+            remaining: count,
+            anchors: mem::take(&mut self.anchors),
+            includes: self.includes,
+            names: self.names.take(),
+        }))
+    }
+
+    fn visit_repetition<V>(
+        &mut self,
+        mut repetition: Repetition<'de>,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let value = visitor.visit_seq(&mut repetition);
+        self.anchors = repetition.anchors;
+        self.names = repetition.names;
+        let value = value?;
+
+        if self.bytes.consume("]") {
+            Ok(value)
+        } else {
+            self.bytes.err(ErrorCode::ExpectedArrayEnd)
+        }
+    }
+
+    /// Handles an `&name value` anchor or a `*name` alias at the current
+    /// position if `anchors` is enabled. Otherwise the visitor is handed
+    /// back to deserialize the value as usual.
+    fn anchored<V, F>(
+        &mut self,
+        visitor: V,
+        deserialize: F,
+    ) -> Result<std::result::Result<V::Value, V>>
+    where
+        V: Visitor<'de>,
+        F: FnOnce(&mut Deserializer<'de>, V) -> Result<V::Value>,
+    {
+        if !self.bytes.exts.contains(Extensions::ANCHORS) {
+            return Ok(Err(visitor));
+        }
+
+        self.bytes.skip_ws()?;
+        let is_newtype_variant_start = self.is_newtype_variant_start();
+        let position = self.bytes;
+
+        if self.bytes.consume("&") {
+            let name = self.bytes.identifier()?;
+            self.bytes.skip_ws()?;
+
+            // The value still starts a newtype variant after its anchor
+            if is_newtype_variant_start {
+                self.newtype_variant = Some(self.bytes.bytes().len());
+            }
+
+            // An anchor is only recorded again when its value is replayed,
+            // so the aliases in the value keep referring to the same anchors
+            let source = self.bytes;
+            if let Some(defined) = self.anchors.values.get(name) {
+                if !std::ptr::eq(defined.bytes(), source.bytes()) {
+                    let name = String::from_utf8_lossy(name).into_owned();
+                    return Err(position.error(ErrorCode::DuplicateAnchor(name)));
+                }
+            }
+
+            // Recorded after the value, which therefore cannot reference itself
+            let value = deserialize(self, visitor)?;
+            self.anchors.values.insert(name, source);
+
+            return Ok(Ok(value));
+        }
+
+        if !self.bytes.consume("*") {
+            return Ok(Err(visitor));
+        }
+
+        let name = self.bytes.identifier()?;
+        let source = match self.anchors.values.get(name) {
+            Some(source) => *source,
+            None => {
+                let name = String::from_utf8_lossy(name).into_owned();
+                return Err(position.error(ErrorCode::UnknownAlias(name)));
+            }
+        };
+
+        if self.anchors.replays >= MAX_ALIAS_REPLAYS {
+            return Err(position.error(ErrorCode::AliasLimitExceeded("too many aliases")));
+        }
+        if self.anchors.depth >= MAX_ALIAS_DEPTH {
+            return Err(position.error(ErrorCode::AliasLimitExceeded("aliases nested too deeply")));
+        }
+
+        let mut alias = Deserializer {
+            bytes: source,
+            input: self.input,
+            comments: None,
+            newtype_variant: Some(source.bytes().len()).filter(|_| is_newtype_variant_start),
+            outer_struct: None,
+            anchors: mem::take(&mut self.anchors),
+            includes: self.includes,
+            names: self.names.take(),
+        };
+        alias.anchors.replays += 1;
+        alias.anchors.depth += 1;
+
+        let value = deserialize(&mut alias, visitor);
+
+        self.anchors = alias.anchors;
+        self.names = alias.names;
+        self.anchors.depth -= 1;
+
+        value.map(Ok)
+    }
+
+    /// Deserializes the file named by an `include!("path")` directive at
+    /// the current position if `include` is enabled. Otherwise the visitor
+    /// is handed back to deserialize the value as usual.
+    fn included<V, F>(
+        &mut self,
+        visitor: V,
+        deserialize: F,
+    ) -> Result<std::result::Result<V::Value, V>>
+    where
+        V: Visitor<'de>,
+        F: FnOnce(&mut Deserializer<'de>, V) -> Result<V::Value>,
+    {
+        if !self.bytes.exts.contains(Extensions::INCLUDE) {
+            return Ok(Err(visitor));
+        }
+
+        self.bytes.skip_ws()?;
+
+        let position = self.bytes;
+        let path = match self.bytes.include()? {
+            Some(path) => path,
+            None => return Ok(Err(visitor)),
+        };
+        let (file, content) = match self
+            .includes
+            .and_then(|includes| includes.get(self.bytes.file(), &path))
+        {
+            Some(file) => file,
+            None => {
+                return Err(position.error(ErrorCode::IncludeFailed {
+                    path,
+                    error: "not loaded with `Includes::load`".to_owned(),
+                }))
+            }
+        };
+
+        if self.anchors.included >= MAX_INCLUDE_REPLAYS {
+            return Err(position.error(ErrorCode::IncludeLimitExceeded));
+        }
+
+        let mut bytes = Bytes::with_file(content.as_bytes(), Some(file))?;
+        bytes.exts |= self.bytes.exts;
+
+        let mut included = Deserializer {
+            bytes,
+            input: content.as_bytes(),
+            comments: None,
+            newtype_variant: None,
+            outer_struct: None,
+            anchors: mem::take(&mut self.anchors),
+            includes: self.includes,
+            names: self.names.take(),
+        };
+        included.anchors.included += 1;
+
+        let value = deserialize(&mut included, visitor);
+        self.anchors = mem::take(&mut included.anchors);
+        self.names = included.names.take();
+        let value = value?;
+
+        included.end()?;
+
+        Ok(Ok(value))
+    }
+
+    /// Parses a bare identifier map key if `unquoted_map_keys` is enabled
+    /// and the next token is an identifier other than a keyword like `true`.
+    fn unquoted_key(&mut self) -> Result<Option<Cow<'de, str>>> {
+        if !self.bytes.exts.contains(Extensions::UNQUOTED_MAP_KEYS)
+            || self.bytes.identifier_len().is_err()
+        {
+            return Ok(None);
+        }
+
+        let mut bytes = self.bytes;
+        let key = bytes.identifier_str()?;
+        if !self.bytes.bytes().starts_with(b"r#") && !parse::is_unquoted_key(&key) {
+            return Ok(None);
+        }
+        self.bytes = bytes;
+
+        Ok(Some(key))
+    }
+
     /// Called from `deserialize_any` when a struct was detected. Decides if
     /// there is a unit, tuple or usual struct and deserializes it
     /// accordingly.
@@ -126,6 +690,12 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        redirected!(self, visitor, |de| de.deserialize_any(visitor));
+
+        if self.is_outer_struct_start()? {
+            return self.deserialize_struct("", &[], visitor);
+        }
+
         if self.bytes.consume_ident("true") {
             return visitor.visit_bool(true);
         } else if self.bytes.consume_ident("false") {
@@ -144,36 +714,68 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             return visitor.visit_f64(std::f64::NAN);
         }
 
+        if self.bytes.check_byte_buf_prefix() {
+            return visitor.visit_byte_buf(self.bytes.byte_buf()?);
+        } else if self.bytes.check_byte_literal() {
+            return visitor.visit_u8(self.bytes.byte()?);
+        }
+
+        if self.bytes.exts.contains(Extensions::VARIANT_PATHS) {
+            self.bytes.path_prefix();
+        }
+
         // `identifier` does not change state if it fails
         let ident = self.bytes.identifier().ok();
+        if let (Some(names), Some(ident)) = (&mut self.names, ident) {
+            names.push(ident);
+        }
 
         if ident.is_some() {
             self.bytes.skip_ws()?;
 
+            if self.bytes.exts.contains(Extensions::BRACED_STRUCTS)
+                && self.bytes.peek_or_eof()? == b'{'
+            {
+                // the name is already consumed
+                return self.deserialize_struct("", &[], visitor);
+            }
+
             return self.handle_any_struct(visitor);
         }
 
+        #[cfg(feature = "datetime")]
+        {
+            if self.bytes.exts.contains(Extensions::DATETIME_LITERALS) {
+                if let Some(literal) = self.bytes.datetime() {
+                    return visitor.visit_borrowed_str(literal);
+                }
+            }
+        }
+
         match self.bytes.peek_or_eof()? {
+            b'(' if self.bytes.exts.contains(Extensions::CONST_EXPRESSIONS) => {
+                // a constant expression in parentheses, otherwise a tuple
+                // or struct
+                let mut bytes = self.bytes;
+                match bytes.any_num() {
+                    Ok(any_num) => {
+                        self.bytes = bytes;
+
+                        visit_any_num(any_num, visitor)
+                    }
+                    Err(_) => self.handle_any_struct(visitor),
+                }
+            }
             b'(' => self.handle_any_struct(visitor),
             b'[' => self.deserialize_seq(visitor),
             b'{' => self.deserialize_map(visitor),
+            b'#' if self.bytes.exts.contains(Extensions::SET_LITERALS) => {
+                self.deserialize_seq(visitor)
+            }
             b'0'..=b'9' | b'+' | b'-' => {
                 let any_num: AnyNum = self.bytes.any_num()?;
 
-                match any_num {
-                    AnyNum::F32(x) => visitor.visit_f32(x),
-                    AnyNum::F64(x) => visitor.visit_f64(x),
-                    AnyNum::I8(x) => visitor.visit_i8(x),
-                    AnyNum::U8(x) => visitor.visit_u8(x),
-                    AnyNum::I16(x) => visitor.visit_i16(x),
-                    AnyNum::U16(x) => visitor.visit_u16(x),
-                    AnyNum::I32(x) => visitor.visit_i32(x),
-                    AnyNum::U32(x) => visitor.visit_u32(x),
-                    AnyNum::I64(x) => visitor.visit_i64(x),
-                    AnyNum::U64(x) => visitor.visit_u64(x),
-                    AnyNum::I128(x) => visitor.visit_i128(x),
-                    AnyNum::U128(x) => visitor.visit_u128(x),
-                }
+                visit_any_num(any_num, visitor)
             }
             b'.' => self.deserialize_f64(visitor),
             b'"' | b'r' => self.deserialize_string(visitor),
@@ -186,6 +788,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        redirected!(self, visitor, |de| de.deserialize_bool(visitor));
+
         visitor.visit_bool(self.bytes.bool()?)
     }
 
@@ -193,90 +797,140 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i8(self.bytes.signed_integer()?)
+        redirected!(self, visitor, |de| de.deserialize_i8(visitor));
+
+        let v = self.bytes.number("i8", Bytes::signed_integer)?;
+
+        visitor.visit_i8(v)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i16(self.bytes.signed_integer()?)
+        redirected!(self, visitor, |de| de.deserialize_i16(visitor));
+
+        let v = self.bytes.number("i16", Bytes::signed_integer)?;
+
+        visitor.visit_i16(v)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i32(self.bytes.signed_integer()?)
+        redirected!(self, visitor, |de| de.deserialize_i32(visitor));
+
+        let v = self.bytes.number("i32", Bytes::signed_integer)?;
+
+        visitor.visit_i32(v)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i64(self.bytes.signed_integer()?)
+        redirected!(self, visitor, |de| de.deserialize_i64(visitor));
+
+        let v = self.bytes.number("i64", Bytes::signed_integer)?;
+
+        visitor.visit_i64(v)
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i128(self.bytes.signed_integer()?)
+        redirected!(self, visitor, |de| de.deserialize_i128(visitor));
+
+        let v = self.bytes.number("i128", Bytes::signed_integer)?;
+
+        visitor.visit_i128(v)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u8(self.bytes.unsigned_integer()?)
+        redirected!(self, visitor, |de| de.deserialize_u8(visitor));
+
+        let v = self.bytes.number("u8", Bytes::byte)?;
+
+        visitor.visit_u8(v)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u16(self.bytes.unsigned_integer()?)
+        redirected!(self, visitor, |de| de.deserialize_u16(visitor));
+
+        let v = self.bytes.number("u16", Bytes::unsigned_integer)?;
+
+        visitor.visit_u16(v)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u32(self.bytes.unsigned_integer()?)
+        redirected!(self, visitor, |de| de.deserialize_u32(visitor));
+
+        let v = self.bytes.number("u32", Bytes::unsigned_integer)?;
+
+        visitor.visit_u32(v)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u64(self.bytes.unsigned_integer()?)
+        redirected!(self, visitor, |de| de.deserialize_u64(visitor));
+
+        let v = self.bytes.number("u64", Bytes::unsigned_integer)?;
+
+        visitor.visit_u64(v)
     }
 
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u128(self.bytes.unsigned_integer()?)
+        redirected!(self, visitor, |de| de.deserialize_u128(visitor));
+
+        let v = self.bytes.number("u128", Bytes::unsigned_integer)?;
+
+        visitor.visit_u128(v)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f32(self.bytes.float()?)
+        redirected!(self, visitor, |de| de.deserialize_f32(visitor));
+
+        let v = self.bytes.number("f32", Bytes::float)?;
+
+        visitor.visit_f32(v)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f64(self.bytes.float()?)
+        redirected!(self, visitor, |de| de.deserialize_f64(visitor));
+
+        let v = self.bytes.number("f64", Bytes::float)?;
+
+        visitor.visit_f64(v)
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        redirected!(self, visitor, |de| de.deserialize_char(visitor));
+
         visitor.visit_char(self.bytes.char()?)
     }
 
@@ -284,6 +938,17 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        redirected!(self, visitor, |de| de.deserialize_str(visitor));
+
+        #[cfg(feature = "datetime")]
+        {
+            if self.bytes.exts.contains(Extensions::DATETIME_LITERALS) {
+                if let Some(literal) = self.bytes.datetime() {
+                    return visitor.visit_str(literal);
+                }
+            }
+        }
+
         match self.bytes.string()? {
             ParsedStr::Allocated(s) => visitor.visit_string(s),
             ParsedStr::Slice(s) => visitor.visit_str(s),
@@ -308,25 +973,17 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let res = {
-            let string = self.bytes.string()?;
-            let base64_str = match string {
-                ParsedStr::Allocated(ref s) => s.as_str(),
-                ParsedStr::Slice(ref s) => s,
-            };
-            base64::decode(base64_str)
-        };
+        redirected!(self, visitor, |de| de.deserialize_byte_buf(visitor));
 
-        match res {
-            Ok(byte_buf) => visitor.visit_byte_buf(byte_buf),
-            Err(err) => self.bytes.err(ErrorCode::Base64Error(err)),
-        }
+        visitor.visit_byte_buf(self.bytes.byte_buf()?)
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        redirected!(self, visitor, |de| de.deserialize_option(visitor));
+
         if self.bytes.consume("None") {
             visitor.visit_none()
         } else if self.bytes.exts.contains(Extensions::IMPLICIT_SOME) {
@@ -339,7 +996,12 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
 
             let v = visitor.visit_some(&mut *self)?;
 
-            self.bytes.skip_ws()?;
+            // pretty `Debug` output ends the value with a comma
+            if self.bytes.exts.contains(Extensions::BRACED_STRUCTS) {
+                self.bytes.comma()?;
+            } else {
+                self.bytes.skip_ws()?;
+            }
 
             if self.bytes.consume(")") {
                 Ok(v)
@@ -356,6 +1018,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        redirected!(self, visitor, |de| de.deserialize_unit(visitor));
+
         if self.bytes.consume("()") {
             visitor.visit_unit()
         } else {
@@ -367,7 +1031,10 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        if self.bytes.consume(name) {
+        redirected!(self, visitor, |de| de
+            .deserialize_unit_struct(name, visitor));
+
+        if self.bytes.consume_struct_name(name) {
             visitor.visit_unit()
         } else {
             self.deserialize_unit(visitor)
@@ -378,11 +1045,38 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        redirected!(self, visitor, |de| de
+            .deserialize_newtype_struct(name, visitor));
+
+        if name == VALUE_NAME {
+            if self.bytes.exts.contains(Extensions::SET_LITERALS)
+                && self.bytes.bytes().starts_with(b"#{")
+            {
+                return visitor.visit_map(SetLiteral {
+                    de: self,
+                    visited: false,
+                });
+            }
+
+            #[cfg(feature = "datetime")]
+            {
+                if self.bytes.exts.contains(Extensions::DATETIME_LITERALS) {
+                    if let Some(literal) = self.bytes.datetime() {
+                        return visitor.visit_map(DateTime {
+                            literal: Some(literal),
+                        });
+                    }
+                }
+            }
+
+            return self.deserialize_any(visitor);
+        }
+
         if self.bytes.exts.contains(Extensions::UNWRAP_NEWTYPES) {
             return visitor.visit_newtype_struct(&mut *self);
         }
 
-        self.bytes.consume(name);
+        self.bytes.consume_struct_name(name);
 
         self.bytes.skip_ws()?;
 
@@ -405,7 +1099,13 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        redirected!(self, visitor, |de| de.deserialize_seq(visitor));
+
         if self.bytes.consume("[") {
+            if let Some(repetition) = self.repetition()? {
+                return self.visit_repetition(repetition, visitor);
+            }
+
             let value = visitor.visit_seq(CommaSeparated::new(b']', &mut self))?;
             self.bytes.comma()?;
 
@@ -414,16 +1114,31 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             } else {
                 self.bytes.err(ErrorCode::ExpectedArrayEnd)
             }
+        } else if self.bytes.exts.contains(Extensions::SET_LITERALS) && self.bytes.consume("#{") {
+            let value = visitor.visit_seq(CommaSeparated::set(&mut self))?;
+            self.bytes.comma()?;
+
+            if self.bytes.consume("}") {
+                Ok(value)
+            } else {
+                self.bytes.err(ErrorCode::ExpectedSetEnd)
+            }
         } else {
             self.bytes.err(ErrorCode::ExpectedArray)
         }
     }
 
-    fn deserialize_tuple<V>(mut self, _len: usize, visitor: V) -> Result<V::Value>
+    fn deserialize_tuple<V>(mut self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        if self.bytes.consume("(") {
+        redirected!(self, visitor, |de| de.deserialize_tuple(len, visitor));
+
+        if self.is_newtype_variant_start() {
+            self.newtype_variant = None;
+
+            visitor.visit_seq(CommaSeparated::new(b')', self))
+        } else if self.bytes.consume("(") {
             let value = visitor.visit_seq(CommaSeparated::new(b')', &mut self))?;
             self.bytes.comma()?;
 
@@ -433,6 +1148,16 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 self.bytes.err(ErrorCode::ExpectedArrayEnd)
             }
         } else {
+            // Fixed size arrays are deserialized as tuples
+            let start = self.bytes;
+
+            if self.bytes.consume("[") {
+                if let Some(repetition) = self.repetition()? {
+                    return self.visit_repetition(repetition, visitor);
+                }
+            }
+
+            self.bytes = start;
             self.bytes.err(ErrorCode::ExpectedArray)
         }
     }
@@ -446,7 +1171,13 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        self.bytes.consume(name);
+        redirected!(self, visitor, |de| de
+            .deserialize_tuple_struct(name, len, visitor));
+
+        if !self.is_newtype_variant_start() {
+            self.bytes.consume_struct_name(name);
+        }
+
         self.deserialize_tuple(len, visitor)
     }
 
@@ -454,6 +1185,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        redirected!(self, visitor, |de| de.deserialize_map(visitor));
+
         if self.bytes.consume("{") {
             let value = visitor.visit_map(CommaSeparated::new(b'}', &mut self))?;
             self.bytes.comma()?;
@@ -471,13 +1204,30 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     fn deserialize_struct<V>(
         mut self,
         name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        self.bytes.consume(name);
+        redirected!(self, visitor, |de| de
+            .deserialize_struct(name, fields, visitor));
+
+        if self.is_newtype_variant_start() {
+            self.newtype_variant = None;
+
+            return visitor.visit_map(CommaSeparated::new(b')', self));
+        }
+
+        if self.is_outer_struct_start()? {
+            self.outer_struct = None;
+
+            return visitor.visit_map(CommaSeparated::outer_struct(self));
+        }
+
+        // An empty name is passed for struct variants, whose name is
+        // already consumed
+        let named = self.bytes.consume_struct_name(name);
 
         self.bytes.skip_ws()?;
 
@@ -490,6 +1240,18 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             } else {
                 self.bytes.err(ErrorCode::ExpectedStructEnd)
             }
+        } else if named
+            && self.bytes.exts.contains(Extensions::BRACED_STRUCTS)
+            && self.bytes.consume("{")
+        {
+            let value = visitor.visit_map(CommaSeparated::braced_struct(&mut self))?;
+            self.bytes.comma()?;
+
+            if self.bytes.consume("}") {
+                Ok(value)
+            } else {
+                self.bytes.err(ErrorCode::ExpectedStructEnd)
+            }
         } else {
             self.bytes.err(ErrorCode::ExpectedStruct)
         }
@@ -497,13 +1259,30 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
 
     fn deserialize_enum<V>(
         self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
+        name: &'static str,
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        redirected!(self, visitor, |de| de
+            .deserialize_enum(name, variants, visitor));
+
+        if self.bytes.exts.contains(Extensions::VARIANT_PATHS) {
+            let start = self.bytes;
+
+            match self.bytes.path_prefix() {
+                Some(path) if path != name => {
+                    return start.err(ErrorCode::ExpectedDifferentEnum {
+                        expected: name,
+                        found: path.into_owned(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
         visitor.visit_enum(Enum::new(self))
     }
 
@@ -511,9 +1290,10 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_str(
-            str::from_utf8(self.bytes.identifier()?).map_err(|e| self.bytes.error(e.into()))?,
-        )
+        match self.bytes.identifier_str()? {
+            Cow::Borrowed(ident) => visitor.visit_str(ident),
+            Cow::Owned(ident) => visitor.visit_string(ident),
+        }
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
@@ -524,18 +1304,74 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     }
 }
 
+fn visit_any_num<'de, V>(any_num: AnyNum, visitor: V) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    match any_num {
+        AnyNum::F32(x) => visitor.visit_f32(x),
+        AnyNum::F64(x) => visitor.visit_f64(x),
+        AnyNum::I8(x) => visitor.visit_i8(x),
+        AnyNum::U8(x) => visitor.visit_u8(x),
+        AnyNum::I16(x) => visitor.visit_i16(x),
+        AnyNum::U16(x) => visitor.visit_u16(x),
+        AnyNum::I32(x) => visitor.visit_i32(x),
+        AnyNum::U32(x) => visitor.visit_u32(x),
+        AnyNum::I64(x) => visitor.visit_i64(x),
+        AnyNum::U64(x) => visitor.visit_u64(x),
+        AnyNum::I128(x) => visitor.visit_i128(x),
+        AnyNum::U128(x) => visitor.visit_u128(x),
+    }
+}
+
 struct CommaSeparated<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
-    terminator: u8,
+    /// `None` for the fields of an outer struct, which end with the input
+    terminator: Option<u8>,
+    is_struct: bool,
     had_comma: bool,
+    index: usize,
+    /// The elements of a set literal so far with their names, to reject
+    /// duplicates
+    elements: Option<HashSet<SetElement<'de>>>,
 }
 
 impl<'a, 'de> CommaSeparated<'a, 'de> {
     fn new(terminator: u8, de: &'a mut Deserializer<'de>) -> Self {
+        de.scan_comments();
+
         CommaSeparated {
             de,
-            terminator,
+            terminator: Some(terminator),
+            is_struct: terminator == b')',
             had_comma: true,
+            index: 0,
+            elements: None,
+        }
+    }
+
+    /// Elements of a `#{..}` set literal.
+    fn set(de: &'a mut Deserializer<'de>) -> Self {
+        CommaSeparated {
+            elements: Some(HashSet::new()),
+            ..CommaSeparated::new(b'}', de)
+        }
+    }
+
+    /// Fields of a struct written with Rust's `Name { .. }` syntax.
+    fn braced_struct(de: &'a mut Deserializer<'de>) -> Self {
+        CommaSeparated {
+            is_struct: true,
+            ..CommaSeparated::new(b'}', de)
+        }
+    }
+
+    /// Fields of an outer struct without parentheses, up to the end of
+    /// the input.
+    fn outer_struct(de: &'a mut Deserializer<'de>) -> Self {
+        CommaSeparated {
+            terminator: None,
+            ..CommaSeparated::new(b')', de)
         }
     }
 
@@ -546,7 +1382,20 @@ impl<'a, 'de> CommaSeparated<'a, 'de> {
     fn has_element(&mut self) -> Result<bool> {
         self.de.bytes.skip_ws()?;
 
-        Ok(self.had_comma && self.de.bytes.peek_or_eof()? != self.terminator)
+        if self.had_comma && self.before_terminator()? {
+            Ok(true)
+        } else {
+            self.de.scan_comments();
+
+            Ok(false)
+        }
+    }
+
+    fn before_terminator(&self) -> Result<bool> {
+        match self.terminator {
+            Some(terminator) => Ok(self.de.bytes.peek_or_eof()? != terminator),
+            None => Ok(self.de.bytes.peek().is_some()),
+        }
     }
 }
 
@@ -558,8 +1407,26 @@ impl<'de, 'a> de::SeqAccess<'de> for CommaSeparated<'a, 'de> {
         T: DeserializeSeed<'de>,
     {
         if self.has_element()? {
+            if self.de.comments.is_some() {
+                let start = self.de.bytes.bytes();
+                self.de.enter_comment_path(start, self.index.to_string());
+            }
+
+            let start = self.de.bytes;
             let res = seed.deserialize(&mut *self.de)?;
 
+            // Sets nested in an element have been checked when it was
+            // deserialized with its type, so they are not checked again
+            // while comparing it.
+            if let (Some(elements), None) = (&mut self.elements, &self.de.names) {
+                if !elements.insert(self.de.set_element(start)?) {
+                    return start.err(ErrorCode::DuplicateSetElement);
+                }
+            }
+
+            self.de.leave_comment_path();
+            self.index += 1;
+
             self.had_comma = self.de.bytes.comma()?;
 
             Ok(Some(res))
@@ -577,12 +1444,21 @@ impl<'de, 'a> de::MapAccess<'de> for CommaSeparated<'a, 'de> {
         K: DeserializeSeed<'de>,
     {
         if self.has_element()? {
-            if self.terminator == b')' {
-                seed.deserialize(&mut IdDeserializer::new(&mut *self.de))
-                    .map(Some)
-            } else {
-                seed.deserialize(&mut *self.de).map(Some)
+            if self.de.comments.is_some() {
+                let start = self.de.bytes.bytes();
+                let key = self.de.key_source(self.is_struct)?;
+                self.de.enter_comment_path(start, key.to_owned());
             }
+
+            let key = if self.is_struct {
+                seed.deserialize(&mut IdDeserializer::new(&mut *self.de))?
+            } else {
+                seed.deserialize(&mut MapKeyDeserializer::new(&mut *self.de))?
+            };
+
+            self.de.skip_comment_scan();
+
+            Ok(Some(key))
         } else {
             Ok(None)
         }
@@ -596,9 +1472,12 @@ impl<'de, 'a> de::MapAccess<'de> for CommaSeparated<'a, 'de> {
 
         if self.de.bytes.consume(":") {
             self.de.bytes.skip_ws()?;
+            self.de.scan_comments();
 
             let res = seed.deserialize(&mut TagDeserializer::new(&mut *self.de))?;
 
+            self.de.leave_comment_path();
+
             self.had_comma = self.de.bytes.comma()?;
 
             Ok(res)
@@ -608,6 +1487,145 @@ impl<'de, 'a> de::MapAccess<'de> for CommaSeparated<'a, 'de> {
     }
 }
 
+/// The elements of a `[expr; N]` repetition, which deserializes the
+/// source of `expr` again for each element. Going through `Value`
+/// instead would lose enum variants.
+struct Repetition<'de> {
+    element: Bytes<'de>,
+    input: &'de [u8],
+    remaining: usize,
+    anchors: Anchors<'de>,
+    includes: Option<&'de Includes>,
+    names: Option<Vec<&'de [u8]>>,
+}
+
+impl<'de> de::SeqAccess<'de> for Repetition<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+
+        let mut element = Deserializer {
+            bytes: self.element,
+            input: self.input,
+            comments: None,
+            newtype_variant: None,
+            outer_struct: None,
+            anchors: mem::take(&mut self.anchors),
+            includes: self.includes,
+            names: self.names.take(),
+        };
+
+        let value = seed.deserialize(&mut element);
+        self.anchors = element.anchors;
+        self.names = element.names;
+        let value = value?;
+
+        // The `;` was found without parsing, so the element has to end there
+        element.bytes.skip_ws()?;
+        if element.bytes.consume(";") {
+            Ok(Some(value))
+        } else {
+            element.bytes.err(ErrorCode::ExpectedComma)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// The single-entry map a set literal is visited as when deserializing a
+/// `Value`, so it can be told apart from a list.
+struct SetLiteral<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    visited: bool,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for SetLiteral<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.visited {
+            return Ok(None);
+        }
+        self.visited = true;
+
+        seed.deserialize(BorrowedStrDeserializer::new(SET_KEY))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(SetElements(&mut *self.de))
+    }
+}
+
+/// The elements of a set literal, which any type visits as a sequence.
+struct SetElements<'a, 'de: 'a>(&'a mut Deserializer<'de>);
+
+impl<'de, 'a> de::Deserializer<'de> for SetElements<'a, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.deserialize_seq(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// The single-entry map a date/time literal is visited as when
+/// deserializing a `Value`, so it can be told apart from a string.
+#[cfg(feature = "datetime")]
+struct DateTime<'de> {
+    literal: Option<&'de str>,
+}
+
+#[cfg(feature = "datetime")]
+impl<'de> de::MapAccess<'de> for DateTime<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.literal.is_none() {
+            return Ok(None);
+        }
+
+        seed.deserialize(BorrowedStrDeserializer::new(DATETIME_KEY))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.literal.take() {
+            Some(literal) => seed.deserialize(BorrowedStrDeserializer::new(literal)),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+}
+
 struct Enum<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
 }
@@ -650,6 +1668,15 @@ impl<'de, 'a> de::VariantAccess<'de> for Enum<'a, 'de> {
         if self.de.bytes.consume("(") {
             self.de.bytes.skip_ws()?;
 
+            if self
+                .de
+                .bytes
+                .exts
+                .contains(Extensions::UNWRAP_VARIANT_NEWTYPES)
+            {
+                self.de.newtype_variant = Some(self.de.bytes.bytes().len());
+            }
+
             let val = seed.deserialize(&mut *self.de)?;
 
             self.de.bytes.comma()?;
@@ -152,7 +152,11 @@ y: 2.0 // 2!
 fn err<T>(kind: ErrorCode, line: usize, col: usize) -> Result<T> {
     Err(Error {
         code: kind,
-        position: Position { line, col },
+        position: Position {
+            line,
+            col,
+            file: None,
+        },
     })
 }
 
@@ -326,3 +330,73 @@ fn test_any_number_precision() {
     assert_eq!(de_any_number("-1."), AnyNum::F32(-1.));
     assert_eq!(de_any_number("0.3"), AnyNum::F64(0.3));
 }
+
+#[test]
+fn test_collect_comments() {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Commented {
+        point: MyStruct,
+        list: Vec<u8>,
+        map: HashMap<String, u8>,
+    }
+
+    let (value, comments) = from_str_with_comments::<Commented>(
+        "// The document
+(
+    /* The point */
+    point: (x: 1, y: 2), // where it is
+    list: [
+        // first
+        1,
+        2, /* second */
+        // dangling
+    ],
+    map: {
+        // the key
+        \"a//b\": /* between */ 3,
+    },
+)",
+    )
+    .unwrap();
+
+    assert_eq!(value.list, vec![1, 2]);
+
+    let expected: HashMap<String, String> = vec![
+        ("", "The document"),
+        ("point", "The point\nwhere it is"),
+        ("list.0", "first"),
+        ("list.1", "second"),
+        ("list", "dangling"),
+        ("map.\"a//b\"", "the key\nbetween"),
+    ]
+    .into_iter()
+    .map(|(path, comment)| (path.to_owned(), comment.to_owned()))
+    .collect();
+    assert_eq!(comments, expected);
+}
+
+#[test]
+fn test_comments_in_literals() {
+    use std::collections::HashMap;
+
+    assert_eq!(
+        from_str_with_comments::<String>("\"http://x\""),
+        Ok(("http://x".to_owned(), HashMap::new()))
+    );
+    assert_eq!(
+        from_str_with_comments::<Option<String>>("Some(\"a//b\") // c"),
+        Ok((
+            Some("a//b".to_owned()),
+            vec![("".to_owned(), "c".to_owned())].into_iter().collect()
+        ))
+    );
+    assert_eq!(
+        from_str_with_comments::<Vec<char>>("['/', '\"'] /* c */"),
+        Ok((
+            vec!['/', '"'],
+            vec![("".to_owned(), "c".to_owned())].into_iter().collect()
+        ))
+    );
+}
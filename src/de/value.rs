@@ -7,7 +7,7 @@ use serde::{
 
 use crate::{
     de,
-    value::{Map, Number, Value},
+    value::{Map, Number, Value, DATETIME_KEY, SET_KEY, VALUE_NAME},
 };
 
 impl std::str::FromStr for Value {
@@ -29,7 +29,7 @@ impl<'de> Deserialize<'de> for Value {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_any(ValueVisitor)
+        deserializer.deserialize_newtype_struct(VALUE_NAME, ValueVisitor)
     }
 }
 
@@ -130,9 +130,9 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         D: Deserializer<'de>,
     {
-        Ok(Value::Option(Some(Box::new(
-            deserializer.deserialize_any(ValueVisitor)?,
-        ))))
+        Ok(Value::Option(Some(Box::new(Value::deserialize(
+            deserializer,
+        )?))))
     }
 
     fn visit_unit<E>(self) -> Result<Self::Value, E>
@@ -171,8 +171,23 @@ impl<'de> Visitor<'de> for ValueVisitor {
     {
         let mut res: Map = Map::new();
 
-        while let Some(entry) = map.next_entry()? {
-            res.insert(entry.0, entry.1);
+        let mut key = map.next_key()?;
+
+        if let Some(Value::String(ref first)) = key {
+            if first == SET_KEY {
+                return Ok(Value::Set(map.next_value()?));
+            }
+        }
+
+        if let Some(Value::String(ref first)) = key {
+            if first == DATETIME_KEY {
+                return Ok(Value::DateTime(map.next_value()?));
+            }
+        }
+
+        while let Some(k) = key {
+            res.insert(k, map.next_value()?);
+            key = map.next_key()?;
         }
 
         Ok(Value::Map(res))
@@ -235,7 +250,11 @@ mod tests {
             Value::from_str("Foo:").unwrap_err(),
             Error {
                 code: ErrorCode::TrailingCharacters,
-                position: Position { col: 4, line: 1 }
+                position: Position {
+                    col: 4,
+                    line: 1,
+                    file: None
+                }
             },
         );
     }
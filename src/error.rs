@@ -1,7 +1,7 @@
 use serde::{de, ser};
 use std::{error::Error as StdError, fmt, io, str::Utf8Error, string::FromUtf8Error};
 
-pub use crate::parse::Position;
+pub use crate::parse::{FileId, Position};
 
 /// This type represents all possible errors that can occur when
 /// serializing or deserializing RON data.
@@ -18,7 +18,10 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum ErrorCode {
     Io(String),
     Message(String),
+    AliasLimitExceeded(&'static str),
     Base64Error(base64::DecodeError),
+    DuplicateAnchor(String),
+    DuplicateSetElement,
     Eof,
     ExpectedArray,
     ExpectedArrayEnd,
@@ -29,12 +32,14 @@ pub enum ErrorCode {
     // ExpectedEnum,
     ExpectedChar,
     ExpectedFloat,
+    ExpectedInclude,
     ExpectedInteger,
     ExpectedOption,
     ExpectedOptionEnd,
     ExpectedMap,
     ExpectedMapColon,
     ExpectedMapEnd,
+    ExpectedSetEnd,
     ExpectedStruct,
     ExpectedStructEnd,
     ExpectedUnit,
@@ -42,16 +47,38 @@ pub enum ErrorCode {
     ExpectedString,
     ExpectedStringEnd,
     ExpectedIdentifier,
+    ExpectedDifferentEnum {
+        expected: &'static str,
+        found: String,
+    },
+
+    IncludeCycle(String),
+    IncludeFailed {
+        path: String,
+        error: String,
+    },
+    IncludeLimitExceeded,
 
     InvalidEscape(&'static str),
+    InvalidExpression(&'static str),
+    InvalidIdentifier(String),
+    InvalidNumberSuffix {
+        expected: &'static str,
+        found: &'static str,
+    },
+    InvalidWriterState(&'static str),
 
     IntegerOutOfBounds,
 
     NoSuchExtension(String),
 
+    RepetitionLimitExceeded,
+
     UnclosedBlockComment,
     UnderscoreAtBeginning,
+    UnrepresentableComment,
     UnexpectedByte(char),
+    UnknownAlias(String),
 
     Utf8Error(Utf8Error),
     TrailingCharacters,
@@ -59,7 +86,13 @@ pub enum ErrorCode {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if (self.position == Position { line: 0, col: 0 }) {
+        if (self.position
+            == Position {
+                line: 0,
+                col: 0,
+                file: None,
+            })
+        {
             write!(f, "{}", self.code)
         } else {
             write!(f, "{}: {}", self.position, self.code)
@@ -72,7 +105,12 @@ impl fmt::Display for ErrorCode {
         match *self {
             ErrorCode::Io(ref s) => f.write_str(s),
             ErrorCode::Message(ref s) => f.write_str(s),
+            ErrorCode::AliasLimitExceeded(s) => write!(f, "Alias limit exceeded: {}", s),
             ErrorCode::Base64Error(ref e) => fmt::Display::fmt(e, f),
+            ErrorCode::DuplicateAnchor(ref name) => {
+                write!(f, "Anchor `&{}` is already defined", name)
+            }
+            ErrorCode::DuplicateSetElement => f.write_str("Duplicate element in set"),
             ErrorCode::Eof => f.write_str("Unexpected end of file"),
             ErrorCode::ExpectedArray => f.write_str("Expected array"),
             ErrorCode::ExpectedArrayEnd => f.write_str("Expected end of array"),
@@ -85,12 +123,14 @@ impl fmt::Display for ErrorCode {
             // ErrorCode::ExpectedEnum => f.write_str("Expected enum"),
             ErrorCode::ExpectedChar => f.write_str("Expected char"),
             ErrorCode::ExpectedFloat => f.write_str("Expected float"),
+            ErrorCode::ExpectedInclude => f.write_str("Expected `include!(\"path\")`"),
             ErrorCode::ExpectedInteger => f.write_str("Expected integer"),
             ErrorCode::ExpectedOption => f.write_str("Expected option"),
             ErrorCode::ExpectedOptionEnd => f.write_str("Expected end of option"),
             ErrorCode::ExpectedMap => f.write_str("Expected map"),
             ErrorCode::ExpectedMapColon => f.write_str("Expected colon"),
             ErrorCode::ExpectedMapEnd => f.write_str("Expected end of map"),
+            ErrorCode::ExpectedSetEnd => f.write_str("Expected end of set"),
             ErrorCode::ExpectedStruct => f.write_str("Expected struct"),
             ErrorCode::ExpectedStructEnd => f.write_str("Expected end of struct"),
             ErrorCode::ExpectedUnit => f.write_str("Expected unit"),
@@ -98,13 +138,40 @@ impl fmt::Display for ErrorCode {
             ErrorCode::ExpectedString => f.write_str("Expected string"),
             ErrorCode::ExpectedStringEnd => f.write_str("Expected string end"),
             ErrorCode::ExpectedIdentifier => f.write_str("Expected identifier"),
+            ErrorCode::ExpectedDifferentEnum {
+                expected,
+                ref found,
+            } => write!(
+                f,
+                "Expected a variant of `{}`, found `{}::`",
+                expected, found
+            ),
+            ErrorCode::IncludeCycle(ref file) => write!(f, "Include cycle through `{}`", file),
+            ErrorCode::IncludeFailed {
+                ref path,
+                ref error,
+            } => write!(f, "Failed to include `{}`: {}", path, error),
+            ErrorCode::IncludeLimitExceeded => f.write_str("Too many files included"),
             ErrorCode::InvalidEscape(_) => f.write_str("Invalid escape sequence"),
+            ErrorCode::InvalidExpression(s) => write!(f, "Invalid constant expression: {}", s),
+            ErrorCode::InvalidIdentifier(ref s) => write!(f, "Invalid identifier {:?}", s),
+            ErrorCode::InvalidNumberSuffix { expected, found } => write!(
+                f,
+                "Expected a number of type `{}`, found suffix `{}`",
+                expected, found
+            ),
+            ErrorCode::InvalidWriterState(s) => write!(f, "Invalid writer state: {}", s),
             ErrorCode::IntegerOutOfBounds => f.write_str("Integer is out of bounds"),
             ErrorCode::NoSuchExtension(_) => f.write_str("No such RON extension"),
+            ErrorCode::RepetitionLimitExceeded => f.write_str("Too many elements repeated"),
             ErrorCode::Utf8Error(ref e) => fmt::Display::fmt(e, f),
             ErrorCode::UnclosedBlockComment => f.write_str("Unclosed block comment"),
             ErrorCode::UnderscoreAtBeginning => f.write_str("Found underscore at the beginning"),
+            ErrorCode::UnrepresentableComment => {
+                f.write_str("Comment cannot be written as a block comment")
+            }
             ErrorCode::UnexpectedByte(_) => f.write_str("Unexpected byte"),
+            ErrorCode::UnknownAlias(ref name) => write!(f, "Unknown alias `*{}`", name),
             ErrorCode::TrailingCharacters => f.write_str("Non-whitespace trailing characters"),
         }
     }
@@ -114,7 +181,11 @@ impl de::Error for Error {
     fn custom<T: fmt::Display>(msg: T) -> Self {
         Error {
             code: ErrorCode::Message(msg.to_string()),
-            position: Position { line: 0, col: 0 },
+            position: Position {
+                line: 0,
+                col: 0,
+                file: None,
+            },
         }
     }
 }
@@ -123,7 +194,11 @@ impl ser::Error for Error {
     fn custom<T: fmt::Display>(msg: T) -> Self {
         Error {
             code: ErrorCode::Message(msg.to_string()),
-            position: Position { line: 0, col: 0 },
+            position: Position {
+                line: 0,
+                col: 0,
+                file: None,
+            },
         }
     }
 }
@@ -146,7 +221,11 @@ impl From<Utf8Error> for Error {
     fn from(e: Utf8Error) -> Self {
         Error {
             code: ErrorCode::Utf8Error(e),
-            position: Position { line: 0, col: 0 },
+            position: Position {
+                line: 0,
+                col: 0,
+                file: None,
+            },
         }
     }
 }
@@ -155,7 +234,11 @@ impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
         Error {
             code: ErrorCode::Io(e.to_string()),
-            position: Position { line: 0, col: 0 },
+            position: Position {
+                line: 0,
+                col: 0,
+                file: None,
+            },
         }
     }
 }
@@ -6,6 +6,21 @@ bitflags! {
     pub struct Extensions: usize {
         const UNWRAP_NEWTYPES = 0x1;
         const IMPLICIT_SOME = 0x2;
+        const UNQUOTED_MAP_KEYS = 0x4;
+        const UNWRAP_VARIANT_NEWTYPES = 0x8;
+        const VARIANT_PATHS = 0x10;
+        const BRACED_STRUCTS = 0x20;
+        const ARRAY_REPETITION = 0x40;
+        const IMPLICIT_OUTER_STRUCT = 0x80;
+        const INDENTED_STRINGS = 0x100;
+        const NUMBER_SUFFIXES = 0x200;
+        #[cfg(feature = "datetime")]
+        const DATETIME_LITERALS = 0x400;
+        const SET_LITERALS = 0x800;
+        const CONST_EXPRESSIONS = 0x1000;
+        const ANCHORS = 0x2000;
+        const INCLUDE = 0x4000;
+        const BYTE_STRINGS = 0x8000;
     }
 }
 
@@ -15,9 +30,82 @@ impl Extensions {
         match ident {
             b"unwrap_newtypes" => Some(Extensions::UNWRAP_NEWTYPES),
             b"implicit_some" => Some(Extensions::IMPLICIT_SOME),
+            b"unquoted_map_keys" => Some(Extensions::UNQUOTED_MAP_KEYS),
+            b"unwrap_variant_newtypes" => Some(Extensions::UNWRAP_VARIANT_NEWTYPES),
+            b"variant_paths" => Some(Extensions::VARIANT_PATHS),
+            b"braced_structs" => Some(Extensions::BRACED_STRUCTS),
+            b"array_repetition" => Some(Extensions::ARRAY_REPETITION),
+            b"implicit_outer_struct" => Some(Extensions::IMPLICIT_OUTER_STRUCT),
+            b"indented_strings" => Some(Extensions::INDENTED_STRINGS),
+            b"number_suffixes" => Some(Extensions::NUMBER_SUFFIXES),
+            #[cfg(feature = "datetime")]
+            b"datetime_literals" => Some(Extensions::DATETIME_LITERALS),
+            b"set_literals" => Some(Extensions::SET_LITERALS),
+            b"const_expressions" => Some(Extensions::CONST_EXPRESSIONS),
+            b"anchors" => Some(Extensions::ANCHORS),
+            b"include" => Some(Extensions::INCLUDE),
+            b"byte_strings" => Some(Extensions::BYTE_STRINGS),
             _ => None,
         }
     }
+
+    /// Returns the idents of the enabled extensions, in the form
+    /// `#![enable(...)]` expects them.
+    pub fn idents(self) -> Vec<&'static str> {
+        let mut idents = Vec::new();
+
+        if self.contains(Extensions::UNWRAP_NEWTYPES) {
+            idents.push("unwrap_newtypes");
+        }
+        if self.contains(Extensions::IMPLICIT_SOME) {
+            idents.push("implicit_some");
+        }
+        if self.contains(Extensions::UNQUOTED_MAP_KEYS) {
+            idents.push("unquoted_map_keys");
+        }
+        if self.contains(Extensions::UNWRAP_VARIANT_NEWTYPES) {
+            idents.push("unwrap_variant_newtypes");
+        }
+        if self.contains(Extensions::VARIANT_PATHS) {
+            idents.push("variant_paths");
+        }
+        if self.contains(Extensions::BRACED_STRUCTS) {
+            idents.push("braced_structs");
+        }
+        if self.contains(Extensions::ARRAY_REPETITION) {
+            idents.push("array_repetition");
+        }
+        if self.contains(Extensions::IMPLICIT_OUTER_STRUCT) {
+            idents.push("implicit_outer_struct");
+        }
+        if self.contains(Extensions::INDENTED_STRINGS) {
+            idents.push("indented_strings");
+        }
+        if self.contains(Extensions::NUMBER_SUFFIXES) {
+            idents.push("number_suffixes");
+        }
+        #[cfg(feature = "datetime")]
+        if self.contains(Extensions::DATETIME_LITERALS) {
+            idents.push("datetime_literals");
+        }
+        if self.contains(Extensions::SET_LITERALS) {
+            idents.push("set_literals");
+        }
+        if self.contains(Extensions::CONST_EXPRESSIONS) {
+            idents.push("const_expressions");
+        }
+        if self.contains(Extensions::ANCHORS) {
+            idents.push("anchors");
+        }
+        if self.contains(Extensions::INCLUDE) {
+            idents.push("include");
+        }
+        if self.contains(Extensions::BYTE_STRINGS) {
+            idents.push("byte_strings");
+        }
+
+        idents
+    }
 }
 
 impl Default for Extensions {
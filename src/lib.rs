@@ -62,6 +62,7 @@ pub mod ser;
 
 pub mod error;
 pub mod value;
+pub mod writer;
 
 pub mod extensions;
 
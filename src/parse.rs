@@ -1,5 +1,7 @@
 use std::{
+    borrow::Cow,
     char::from_u32 as char_from_u32,
+    convert::TryFrom,
     fmt::{Display, Formatter, Result as FmtResult},
     str::{from_utf8, from_utf8_unchecked, FromStr},
 };
@@ -9,9 +11,11 @@ use crate::extensions::Extensions;
 
 const DIGITS: &[u8] = b"0123456789ABCDEFabcdef_";
 const FLOAT_CHARS: &[u8] = b"0123456789.+-eE";
-const IDENT_FIRST: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_";
-const IDENT_CHAR: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_0123456789";
 const WHITE_SPACE: &[u8] = b"\n\t\r ";
+const NUMBER_SUFFIXES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32",
+    "f64",
+];
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum AnyNum {
@@ -29,6 +33,225 @@ pub enum AnyNum {
     U128(u128),
 }
 
+impl AnyNum {
+    /// The narrowest type that fits `x`.
+    fn from_i128(x: i128) -> AnyNum {
+        if let Ok(x) = i8::try_from(x) {
+            AnyNum::I8(x)
+        } else if let Ok(x) = i16::try_from(x) {
+            AnyNum::I16(x)
+        } else if let Ok(x) = i32::try_from(x) {
+            AnyNum::I32(x)
+        } else if let Ok(x) = i64::try_from(x) {
+            AnyNum::I64(x)
+        } else {
+            AnyNum::I128(x)
+        }
+    }
+
+    /// The narrowest type that fits `x`.
+    fn from_u128(x: u128) -> AnyNum {
+        if let Ok(x) = u8::try_from(x) {
+            AnyNum::U8(x)
+        } else if let Ok(x) = u16::try_from(x) {
+            AnyNum::U16(x)
+        } else if let Ok(x) = u32::try_from(x) {
+            AnyNum::U32(x)
+        } else if let Ok(x) = u64::try_from(x) {
+            AnyNum::U64(x)
+        } else {
+            AnyNum::U128(x)
+        }
+    }
+
+    /// An `f32` if it holds `x` exactly, an `f64` otherwise.
+    // We are not doing float comparisons here in the traditional sense.
+    // Instead, this code checks if a f64 fits inside an f32.
+    #[allow(clippy::float_cmp)]
+    fn from_f64(x: f64) -> AnyNum {
+        if x == x as f32 as f64 {
+            AnyNum::F32(x as f32)
+        } else {
+            AnyNum::F64(x)
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match *self {
+            AnyNum::F32(x) => Some(x as f64),
+            AnyNum::F64(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i128> {
+        match *self {
+            AnyNum::I8(x) => Some(x as i128),
+            AnyNum::U8(x) => Some(x as i128),
+            AnyNum::I16(x) => Some(x as i128),
+            AnyNum::U16(x) => Some(x as i128),
+            AnyNum::I32(x) => Some(x as i128),
+            AnyNum::U32(x) => Some(x as i128),
+            AnyNum::I64(x) => Some(x as i128),
+            AnyNum::U64(x) => Some(x as i128),
+            AnyNum::I128(x) => Some(x),
+            AnyNum::U128(x) => i128::try_from(x).ok(),
+            AnyNum::F32(_) | AnyNum::F64(_) => None,
+        }
+    }
+
+    fn as_uint(&self) -> Option<u128> {
+        match *self {
+            AnyNum::U128(x) => Some(x),
+            _ => self.as_int().and_then(|x| u128::try_from(x).ok()),
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        match *self {
+            AnyNum::U128(x) => x as f64,
+            _ => self
+                .as_float()
+                .or_else(|| self.as_int().map(|x| x as f64))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Converts the number to the type of a number suffix.
+    fn with_suffix(self, suffix: &'static str) -> std::result::Result<AnyNum, ErrorCode> {
+        let float = self.as_float();
+        let int = self.as_int();
+        let uint = self.as_uint();
+
+        let num = match suffix {
+            "f32" | "f64" => {
+                let x = self.to_f64();
+
+                return Ok(if suffix == "f32" {
+                    AnyNum::F32(x as f32)
+                } else {
+                    AnyNum::F64(x)
+                });
+            }
+            _ if float.is_some() => {
+                return Err(ErrorCode::InvalidNumberSuffix {
+                    expected: "f64",
+                    found: suffix,
+                })
+            }
+            "i8" => int.and_then(|x| i8::try_from(x).ok()).map(AnyNum::I8),
+            "i16" => int.and_then(|x| i16::try_from(x).ok()).map(AnyNum::I16),
+            "i32" => int.and_then(|x| i32::try_from(x).ok()).map(AnyNum::I32),
+            "i64" => int.and_then(|x| i64::try_from(x).ok()).map(AnyNum::I64),
+            "i128" => int.map(AnyNum::I128),
+            "u8" => uint.and_then(|x| u8::try_from(x).ok()).map(AnyNum::U8),
+            "u16" => uint.and_then(|x| u16::try_from(x).ok()).map(AnyNum::U16),
+            "u32" => uint.and_then(|x| u32::try_from(x).ok()).map(AnyNum::U32),
+            "u64" => uint.and_then(|x| u64::try_from(x).ok()).map(AnyNum::U64),
+            _ => uint.map(AnyNum::U128),
+        };
+
+        num.ok_or(ErrorCode::IntegerOutOfBounds)
+    }
+}
+
+/// Evaluates the operation on two numbers of unknown type. Integers are
+/// computed as `i128`, or as `u128` if they do not fit, and the result is
+/// given the narrowest type that fits it, like a number literal.
+impl Operand for AnyNum {
+    fn apply(self, op: BinOp, rhs: Self) -> std::result::Result<Self, ErrorCode> {
+        if self.as_float().is_some() || rhs.as_float().is_some() {
+            return self.to_f64().apply(op, rhs.to_f64()).map(AnyNum::from_f64);
+        }
+
+        if let (Some(x), Some(y)) = (self.as_int(), rhs.as_int()) {
+            match x.apply(op, y) {
+                Ok(z) => return Ok(AnyNum::from_i128(z)),
+                Err(ErrorCode::IntegerOutOfBounds) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        match (self.as_uint(), rhs.as_uint()) {
+            (Some(x), Some(y)) => x.apply(op, y).map(AnyNum::from_u128),
+            _ => Err(ErrorCode::IntegerOutOfBounds),
+        }
+    }
+}
+
+/// A binary operator of `const_expressions`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinOp {
+    Or,
+    Xor,
+    And,
+    Shl,
+    Shr,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Or => "|",
+            BinOp::Xor => "^",
+            BinOp::And => "&",
+            BinOp::Shl => "<<",
+            BinOp::Shr => ">>",
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Rem => "%",
+        }
+    }
+
+    /// How strongly the operator binds, as in Rust.
+    fn precedence(self) -> u8 {
+        match self {
+            BinOp::Or => 1,
+            BinOp::Xor => 2,
+            BinOp::And => 3,
+            BinOp::Shl | BinOp::Shr => 4,
+            BinOp::Add | BinOp::Sub => 5,
+            BinOp::Mul | BinOp::Div | BinOp::Rem => 6,
+        }
+    }
+}
+
+/// A number type constant expressions are evaluated in.
+pub trait Operand: Sized {
+    /// Returns `lhs op rhs`, or an error on overflow.
+    fn apply(self, op: BinOp, rhs: Self) -> std::result::Result<Self, ErrorCode>;
+}
+
+macro_rules! impl_float_operand {
+    ($($ty:ident)*) => {
+        $(
+            impl Operand for $ty {
+                fn apply(self, op: BinOp, rhs: Self) -> std::result::Result<Self, ErrorCode> {
+                    match op {
+                        BinOp::Add => Ok(self + rhs),
+                        BinOp::Sub => Ok(self - rhs),
+                        BinOp::Mul => Ok(self * rhs),
+                        BinOp::Div => Ok(self / rhs),
+                        BinOp::Rem => Ok(self % rhs),
+                        _ => Err(ErrorCode::InvalidExpression(
+                            "bit operators need integers",
+                        )),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_float_operand!(f32 f64);
+
 #[derive(Clone, Copy, Debug)]
 pub struct Bytes<'a> {
     /// Bits set according to `Extension` enum.
@@ -36,15 +259,23 @@ pub struct Bytes<'a> {
     bytes: &'a [u8],
     column: usize,
     line: usize,
+    /// The included file being parsed, if any.
+    file: Option<FileId>,
 }
 
 impl<'a> Bytes<'a> {
     pub fn new(bytes: &'a [u8]) -> Result<Self> {
+        Bytes::with_file(bytes, None)
+    }
+
+    /// Like `new`, with errors located in the included file `file`.
+    pub fn with_file(bytes: &'a [u8], file: Option<FileId>) -> Result<Self> {
         let mut b = Bytes {
             bytes,
             column: 1,
             exts: Extensions::empty(),
             line: 1,
+            file,
         };
 
         b.skip_ws()?;
@@ -75,7 +306,8 @@ impl<'a> Bytes<'a> {
         if self.peek_or_eof()? == b'\n' {
             self.line += 1;
             self.column = 1;
-        } else {
+        } else if !is_utf8_continuation(self.bytes[0]) {
+            // Columns count chars, not bytes
             self.column += 1;
         }
 
@@ -156,18 +388,139 @@ impl<'a> Bytes<'a> {
         res
     }
 
+    /// Parses a number, as the narrowest type that fits it unless a
+    /// number suffix gives its type. If `const_expressions` is enabled,
+    /// this can be a constant expression, whose type a suffix on any of
+    /// its numbers gives.
     pub fn any_num(&mut self) -> Result<AnyNum> {
-        // We are not doing float comparisons here in the traditional sense.
-        // Instead, this code checks if a f64 fits inside an f32.
-        #[allow(clippy::float_cmp)]
-        fn any_float(f: f64) -> Result<AnyNum> {
-            if f == f as f32 as f64 {
-                Ok(AnyNum::F32(f as f32))
-            } else {
-                Ok(AnyNum::F64(f))
+        let mut suffix = None;
+        let num = self.const_expr(
+            &mut |bytes: &mut Self| {
+                let num = bytes.guess_num()?;
+                let start = *bytes;
+
+                match bytes.number_suffix() {
+                    Some(found) => match suffix.replace(found) {
+                        Some(expected) if expected != found => {
+                            start.err(ErrorCode::InvalidNumberSuffix { expected, found })
+                        }
+                        _ => num.with_suffix(found).map_err(|e| start.error(e)),
+                    },
+                    None => Ok(num),
+                }
+            },
+            0,
+        )?;
+
+        match suffix {
+            Some(suffix) => num.with_suffix(suffix).map_err(|e| self.error(e)),
+            None => Ok(num),
+        }
+    }
+
+    /// Parses a number with `operand`, which may be followed by a suffix of
+    /// type `ty`. If `const_expressions` is enabled, this can be a constant
+    /// expression of such numbers, which is evaluated in `T`.
+    pub fn number<T: Operand>(
+        &mut self,
+        ty: &'static str,
+        operand: fn(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        self.const_expr(
+            &mut |bytes: &mut Self| {
+                let num = operand(bytes)?;
+                bytes.expect_number_suffix(ty)?;
+
+                Ok(num)
+            },
+            0,
+        )
+    }
+
+    /// Parses the operations of a constant expression which bind stronger
+    /// than `min_precedence`, on operands parsed by `operand`. Errors point
+    /// at the operator whose operation fails.
+    fn const_expr<T: Operand>(
+        &mut self,
+        operand: &mut impl FnMut(&mut Self) -> Result<T>,
+        min_precedence: u8,
+    ) -> Result<T> {
+        let mut lhs = self.const_operand(operand)?;
+
+        while let Some((at, op)) = self.bin_op()? {
+            if op.precedence() <= min_precedence {
+                break;
             }
+
+            *self = at;
+            self.advance(op.symbol().len())?;
+            self.skip_ws()?;
+
+            let rhs = self.const_expr(operand, op.precedence())?;
+            lhs = lhs.apply(op, rhs).map_err(|e| at.error(e))?;
         }
 
+        Ok(lhs)
+    }
+
+    fn const_operand<T: Operand>(
+        &mut self,
+        operand: &mut impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        if !self.exts.contains(Extensions::CONST_EXPRESSIONS) || !self.consume("(") {
+            return operand(self);
+        }
+
+        self.skip_ws()?;
+        let num = self.const_expr(operand, 0)?;
+        self.skip_ws()?;
+
+        if self.consume(")") {
+            Ok(num)
+        } else {
+            self.err(ErrorCode::InvalidExpression("expected `)`"))
+        }
+    }
+
+    /// Returns the operator of a constant expression which follows after
+    /// whitespace, if `const_expressions` is enabled, with the position of
+    /// the operator.
+    fn bin_op(&self) -> Result<Option<(Self, BinOp)>> {
+        if !self.exts.contains(Extensions::CONST_EXPRESSIONS) {
+            return Ok(None);
+        }
+
+        // Not `skip_ws`, which takes any `/` for the start of a comment
+        let mut at = *self;
+        loop {
+            let ws = at.next_bytes_contained_in(WHITE_SPACE);
+            at.advance(ws)?;
+
+            if !(at.test_for("//") || at.test_for("/*")) {
+                break;
+            }
+            at.skip_comment()?;
+        }
+
+        let op = match at.bytes {
+            [b'<', b'<', ..] => BinOp::Shl,
+            [b'>', b'>', ..] => BinOp::Shr,
+            [b'|', ..] => BinOp::Or,
+            [b'^', ..] => BinOp::Xor,
+            [b'&', ..] => BinOp::And,
+            [b'+', ..] => BinOp::Add,
+            [b'-', ..] => BinOp::Sub,
+            [b'*', ..] => BinOp::Mul,
+            [b'/', b'/', ..] | [b'/', b'*', ..] => return Ok(None),
+            [b'/', ..] => BinOp::Div,
+            [b'%', ..] => BinOp::Rem,
+            _ => return Ok(None),
+        };
+
+        Ok(Some((at, op)))
+    }
+
+    fn guess_num(&mut self) -> Result<AnyNum> {
         let bytes_backup = self.bytes;
 
         let first_byte = self.peek_or_eof()?;
@@ -177,68 +530,59 @@ impl<'a> Bytes<'a> {
         if is_float {
             let f = self.float::<f64>()?;
 
-            any_float(f)
-        } else {
-            let max_u8 = std::u8::MAX as u128;
-            let max_u16 = std::u16::MAX as u128;
-            let max_u32 = std::u32::MAX as u128;
-            let max_u64 = std::u64::MAX as u128;
-
-            let min_i8 = std::i8::MIN as i128;
-            let max_i8 = std::i8::MAX as i128;
-            let min_i16 = std::i16::MIN as i128;
-            let max_i16 = std::i16::MAX as i128;
-            let min_i32 = std::i32::MIN as i128;
-            let max_i32 = std::i32::MAX as i128;
-            let min_i64 = std::i64::MIN as i128;
-            let max_i64 = std::i64::MAX as i128;
-
-            if is_signed {
-                match self.signed_integer::<i128>() {
-                    Ok(x) => {
-                        if x >= min_i8 && x <= max_i8 {
-                            Ok(AnyNum::I8(x as i8))
-                        } else if x >= min_i16 && x <= max_i16 {
-                            Ok(AnyNum::I16(x as i16))
-                        } else if x >= min_i32 && x <= max_i32 {
-                            Ok(AnyNum::I32(x as i32))
-                        } else if x >= min_i64 && x <= max_i64 {
-                            Ok(AnyNum::I64(x as i64))
-                        } else {
-                            Ok(AnyNum::I128(x))
-                        }
-                    }
-                    Err(_) => {
-                        self.bytes = bytes_backup;
+            Ok(AnyNum::from_f64(f))
+        } else if is_signed {
+            match self.signed_integer::<i128>() {
+                Ok(x) => Ok(AnyNum::from_i128(x)),
+                Err(_) => {
+                    self.bytes = bytes_backup;
 
-                        any_float(self.float::<f64>()?)
-                    }
+                    Ok(AnyNum::from_f64(self.float::<f64>()?))
                 }
-            } else {
-                match self.unsigned_integer::<u128>() {
-                    Ok(x) => {
-                        if x <= max_u8 {
-                            Ok(AnyNum::U8(x as u8))
-                        } else if x <= max_u16 {
-                            Ok(AnyNum::U16(x as u16))
-                        } else if x <= max_u32 {
-                            Ok(AnyNum::U32(x as u32))
-                        } else if x <= max_u64 {
-                            Ok(AnyNum::U64(x as u64))
-                        } else {
-                            Ok(AnyNum::U128(x))
-                        }
-                    }
-                    Err(_) => {
-                        self.bytes = bytes_backup;
+            }
+        } else {
+            match self.unsigned_integer::<u128>() {
+                Ok(x) => Ok(AnyNum::from_u128(x)),
+                Err(_) => {
+                    self.bytes = bytes_backup;
 
-                        any_float(self.float::<f64>()?)
-                    }
+                    Ok(AnyNum::from_f64(self.float::<f64>()?))
                 }
             }
         }
     }
 
+    /// Consumes the suffix of a number literal, like `u8` in `5u8`, if
+    /// `number_suffixes` is enabled. Returns the type of the number, with
+    /// `usize` and `isize` as their 64-bit types.
+    fn number_suffix(&mut self) -> Option<&'static str> {
+        if !self.exts.contains(Extensions::NUMBER_SUFFIXES) {
+            return None;
+        }
+
+        let suffix = *NUMBER_SUFFIXES.iter().find(|s| self.check_ident(s))?;
+        let _ = self.advance(suffix.len());
+
+        Some(match suffix {
+            "isize" => "i64",
+            "usize" => "u64",
+            suffix => suffix,
+        })
+    }
+
+    /// Consumes the suffix of a number literal of type `ty`, if there is one.
+    pub fn expect_number_suffix(&mut self, ty: &'static str) -> Result<()> {
+        let start = *self;
+
+        match self.number_suffix() {
+            Some(suffix) if suffix != ty => start.err(ErrorCode::InvalidNumberSuffix {
+                expected: ty,
+                found: suffix,
+            }),
+            _ => Ok(()),
+        }
+    }
+
     pub fn bool(&mut self) -> Result<bool> {
         if self.consume("true") {
             Ok(true)
@@ -249,8 +593,8 @@ impl<'a> Bytes<'a> {
         }
     }
 
-    pub fn bytes(&self) -> &[u8] {
-        &self.bytes
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
     }
 
     pub fn char(&mut self) -> Result<char> {
@@ -315,8 +659,9 @@ impl<'a> Bytes<'a> {
 
     fn check_ident_char(&self, index: usize) -> bool {
         self.bytes
-            .get(index)
-            .map_or(false, |b| IDENT_CHAR.contains(b))
+            .get(index..)
+            .and_then(first_char)
+            .is_some_and(is_ident_other_char)
     }
 
     /// Should only be used on a working copy
@@ -385,6 +730,7 @@ impl<'a> Bytes<'a> {
             position: Position {
                 line: self.line,
                 col: self.column,
+                file: self.file,
             },
         }
     }
@@ -396,7 +742,9 @@ impl<'a> Bytes<'a> {
 
     /// Returns the extensions bit mask.
     fn extensions(&mut self) -> Result<Extensions> {
-        if self.peek() != Some(b'#') {
+        // `#{` starts a set literal, not an attribute
+        let is_set = self.exts.contains(Extensions::SET_LITERALS) && self.test_for("#{");
+        if self.peek() != Some(b'#') || is_set {
             return Ok(Extensions::empty());
         }
 
@@ -451,7 +799,7 @@ impl<'a> Bytes<'a> {
             }
         }
 
-        let num_bytes = self.next_bytes_contained_in(FLOAT_CHARS);
+        let num_bytes = self.float_len(0);
 
         let s = unsafe { from_utf8_unchecked(&self.bytes[0..num_bytes]) };
         let res = FromStr::from_str(s).map_err(|_| self.error(ErrorCode::ExpectedFloat));
@@ -461,32 +809,90 @@ impl<'a> Bytes<'a> {
         res
     }
 
+    /// Parses an identifier, either plain or raw. The `r#` of a raw
+    /// identifier is not part of the returned identifier, but its escapes
+    /// are, see `identifier_str`.
     pub fn identifier(&mut self) -> Result<&'a [u8]> {
         let bytes = self.identifier_len()?;
         let ident = &self.bytes[..bytes];
         let _ = self.advance(bytes);
 
-        Ok(ident)
+        Ok(ident.strip_prefix(b"r#").unwrap_or(ident))
+    }
+
+    /// Parses an identifier like `identifier` and resolves the escapes of
+    /// a raw identifier.
+    pub fn identifier_str(&mut self) -> Result<Cow<'a, str>> {
+        let start = *self;
+        let ident = self.identifier()?;
+
+        if !ident.contains(&b'\\') {
+            return from_utf8(ident)
+                .map(Cow::Borrowed)
+                .map_err(|e| start.error(e.into()));
+        }
+
+        let mut raw = start;
+        let _ = raw.advance(2);
+        let mut name = String::new();
+
+        while raw.bytes.len() > self.bytes.len() {
+            if raw.consume("\\") {
+                name.push(raw.parse_escape()?);
+            } else if let Some(c) = first_char(raw.bytes) {
+                name.push(c);
+                let _ = raw.advance(c.len_utf8());
+            }
+        }
+
+        Ok(Cow::Owned(name))
     }
 
     pub fn identifier_len(&self) -> Result<usize> {
         let next = self.peek_or_eof()?;
-        if IDENT_FIRST.contains(&next) {
+        if first_char(self.bytes).is_some_and(is_ident_first_char) {
+            // If the next two bytes signify the start of a byte or hex string
+            // or a byte literal, return an error.
+            if self.check_byte_buf_prefix() || self.check_byte_literal() {
+                return self.err(ErrorCode::ExpectedIdentifier);
+            }
+
             // If the next two bytes signify the start of a raw string literal,
-            // return an error.
+            // return an error. `r#` followed by anything else starts
+            // a raw identifier.
             if next == b'r' {
                 let second = self
                     .bytes
                     .get(1)
                     .ok_or_else(|| self.error(ErrorCode::Eof))?;
-                if *second == b'"' || *second == b'#' {
+                if *second == b'"' {
                     return self.err(ErrorCode::ExpectedIdentifier);
+                } else if *second == b'#' {
+                    let mut raw = *self;
+                    let _ = raw.advance(2);
+
+                    loop {
+                        if raw.consume("\\") {
+                            raw.parse_escape()?;
+                        } else if let Some(c) =
+                            first_char(raw.bytes).filter(|&c| is_ident_raw_char(c))
+                        {
+                            let _ = raw.advance(c.len_utf8());
+                        } else {
+                            break;
+                        }
+                    }
+
+                    let bytes = self.bytes.len() - raw.bytes.len();
+                    if bytes == 2 {
+                        return self.err(ErrorCode::ExpectedIdentifier);
+                    }
+
+                    return Ok(bytes);
                 }
             }
 
-            let bytes = self.next_bytes_contained_in(IDENT_CHAR);
-
-            Ok(bytes)
+            Ok(chars_len(self.bytes, is_ident_other_char))
         } else {
             self.err(ErrorCode::ExpectedIdentifier)
         }
@@ -505,12 +911,7 @@ impl<'a> Bytes<'a> {
                 b'+' | b'-' => 1,
                 _ => 0,
             };
-            let flen = self
-                .bytes
-                .iter()
-                .skip(skip)
-                .take_while(|b| FLOAT_CHARS.contains(b))
-                .count();
+            let flen = self.float_len(skip) - skip;
             let ilen = self
                 .bytes
                 .iter()
@@ -523,6 +924,22 @@ impl<'a> Bytes<'a> {
         }
     }
 
+    /// Returns the length of the float chars from `start` on, where a sign
+    /// only follows an exponent's `e`, so `2-1` ends before the `-`.
+    fn float_len(&self, start: usize) -> usize {
+        let mut len = start;
+        while let Some(&b) = self.bytes.get(len) {
+            let is_sign = b == b'+' || b == b'-';
+            let after_exponent = len > 0 && (self.bytes[len - 1] | 0x20) == b'e';
+            if !FLOAT_CHARS.contains(&b) || (is_sign && len > 0 && !after_exponent) {
+                break;
+            }
+            len += 1;
+        }
+
+        len
+    }
+
     pub fn skip_ws(&mut self) -> Result<()> {
         while self.peek().map_or(false, |c| WHITE_SPACE.contains(&c)) {
             let _ = self.advance_single();
@@ -566,18 +983,128 @@ impl<'a> Bytes<'a> {
     }
 
     pub fn string(&mut self) -> Result<ParsedStr<'_>> {
-        if self.consume("\"") {
-            self.escaped_string()
+        let indented = self.exts.contains(Extensions::INDENTED_STRINGS);
+
+        let s = if self.consume("\"") {
+            match self.string_indent().filter(|_| indented) {
+                Some(indent) => return self.indented_string(indent).map(ParsedStr::Allocated),
+                None => self.escaped_string()?,
+            }
         } else if self.consume("r") {
-            self.raw_string()
+            self.raw_string()?
         } else {
-            self.err(ErrorCode::ExpectedString)
+            return self.err(ErrorCode::ExpectedString);
+        };
+
+        let unindented = match s {
+            ParsedStr::Slice(s) if indented => unindent(s),
+            _ => None,
+        };
+
+        Ok(unindented.map_or(s, ParsedStr::Allocated))
+    }
+
+    /// Returns the indentation of an escaped string whose `"` was just
+    /// consumed, if its source starts with a line break: the fewest spaces
+    /// and tabs that a non-blank line of the source starts with.
+    ///
+    /// The source is used rather than the parsed string, so escaped line
+    /// breaks do not start a line.
+    fn string_indent(&self) -> Option<usize> {
+        let mut end = 0;
+        while *self.bytes.get(end)? != b'"' {
+            end += if self.bytes[end] == b'\\' { 2 } else { 1 };
         }
+
+        let source = &self.bytes[..end];
+        let source = source
+            .strip_prefix(b"\n")
+            .or_else(|| source.strip_prefix(b"\r\n"))?;
+
+        let is_blank = |line: &&[u8]| line.iter().all(u8::is_ascii_whitespace);
+        let leading = |line: &[u8]| {
+            line.iter()
+                .take_while(|&&b| b == b' ' || b == b'\t')
+                .count()
+        };
+
+        let mut lines: Vec<_> = source.split(|&b| b == b'\n').collect();
+        if lines.len() > 1 && lines.last().is_some_and(is_blank) {
+            lines.pop();
+        }
+
+        let indent = lines
+            .into_iter()
+            .filter(|line| !is_blank(line))
+            .map(leading)
+            .min()
+            .unwrap_or(0);
+
+        Some(indent)
     }
 
-    fn escaped_string(&mut self) -> Result<ParsedStr<'_>> {
-        use std::iter::repeat;
+    /// Parses an escaped string which starts with a line break, removing
+    /// the line break, `indent` spaces and tabs from the start of each line
+    /// of the source and the last line if it is blank, see `unindent`.
+    fn indented_string(&mut self, indent: usize) -> Result<String> {
+        if !self.consume("\n") {
+            let _ = self.consume("\r\n");
+        }
+
+        let mut s = String::new();
+        let mut line_start = None;
 
+        loop {
+            let whitespace = self
+                .bytes
+                .iter()
+                .take(indent)
+                .take_while(|&&b| b == b' ' || b == b'\t')
+                .count();
+            let _ = self.advance(whitespace);
+
+            let mut is_blank = true;
+
+            loop {
+                match self.peek() {
+                    None => return self.err(ErrorCode::ExpectedStringEnd),
+                    Some(b'"') => {
+                        let _ = self.advance_single();
+
+                        if let (Some(line_start), true) = (line_start, is_blank) {
+                            s.truncate(line_start);
+                        }
+
+                        return Ok(s);
+                    }
+                    Some(b'\n') => {
+                        let _ = self.advance_single();
+                        line_start = Some(s.len());
+                        s.push('\n');
+
+                        break;
+                    }
+                    Some(b'\\') => {
+                        let _ = self.advance_single();
+                        is_blank = false;
+
+                        if !self.skip_line_continuation() {
+                            s.push(self.parse_escape()?);
+                        }
+                    }
+                    Some(_) => {
+                        let c = first_char(self.bytes)
+                            .ok_or_else(|| self.error(ErrorCode::ExpectedStringEnd))?;
+                        let _ = self.advance(c.len_utf8());
+                        is_blank &= c == ' ' || c == '\t' || c == '\r';
+                        s.push(c);
+                    }
+                }
+            }
+        }
+    }
+
+    fn escaped_string(&mut self) -> Result<ParsedStr<'_>> {
         let (i, end_or_escape) = self
             .bytes
             .iter()
@@ -599,13 +1126,15 @@ impl<'a> Bytes<'a> {
 
             loop {
                 let _ = self.advance(i + 1);
-                let character = self.parse_escape()?;
-                match character.len_utf8() {
-                    1 => s.push(character as u8),
-                    len => {
-                        let start = s.len();
-                        s.extend(repeat(0).take(len));
-                        character.encode_utf8(&mut s[start..]);
+                if !self.skip_line_continuation() {
+                    let character = self.parse_escape()?;
+                    match character.len_utf8() {
+                        1 => s.push(character as u8),
+                        len => {
+                            let start = s.len();
+                            s.resize(start + len, 0);
+                            character.encode_utf8(&mut s[start..]);
+                        }
                     }
                 }
 
@@ -655,6 +1184,275 @@ impl<'a> Bytes<'a> {
         Ok(ParsedStr::Slice(s))
     }
 
+    /// Parses a byte buffer written as a base64 string, a `b"..."` byte
+    /// string, a `x"..."` hex string or a list of bytes.
+    pub fn byte_buf(&mut self) -> Result<Vec<u8>> {
+        if self.check_byte_buf_prefix() {
+            if self.consume("b\"") {
+                self.byte_string()
+            } else {
+                let _ = self.advance(2);
+                self.hex_string()
+            }
+        } else if self.consume("[") {
+            self.byte_list()
+        } else {
+            let string = self.string()?;
+            let base64_str = match string {
+                ParsedStr::Allocated(ref s) => s.as_str(),
+                ParsedStr::Slice(s) => s,
+            };
+
+            base64::decode(base64_str).map_err(|e| self.error(ErrorCode::Base64Error(e)))
+        }
+    }
+
+    /// Consumes the struct or variant name `ident`, written either plainly
+    /// or as a raw identifier.
+    pub fn consume_struct_name(&mut self, ident: &str) -> bool {
+        if self.consume(ident) {
+            return true;
+        }
+
+        let mut raw = *self;
+        if !raw.test_for("r#") {
+            return false;
+        }
+
+        match raw.identifier_str() {
+            Ok(name) if name == ident => {
+                *self = raw;
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Consumes a `Type::` path prefix of an enum variant and returns the
+    /// type name, if the next identifier is followed by `::`.
+    pub fn path_prefix(&mut self) -> Option<Cow<'a, str>> {
+        let mut path = *self;
+        let ident = path.identifier_str().ok()?;
+
+        if path.consume("::") {
+            *self = path;
+
+            Some(ident)
+        } else {
+            None
+        }
+    }
+
+    /// Consumes an RFC 3339 date, time or date-time literal, such as
+    /// `2024-05-01T12:00:00Z`, and returns it.
+    #[cfg(feature = "datetime")]
+    pub fn datetime(&mut self) -> Option<&'a str> {
+        let len = datetime_len(self.bytes)?;
+        let literal = from_utf8(&self.bytes[..len]).ok()?;
+        self.advance(len).ok()?;
+
+        Some(literal)
+    }
+
+    /// Returns the length of the string, byte string or char literal
+    /// starting at the current position, if there is one.
+    fn literal_len(&self) -> Option<usize> {
+        let mut literal = *self;
+        let is_literal = match self.peek() {
+            None => false,
+            _ if self.check_byte_buf_prefix() => literal.byte_buf().is_ok(),
+            Some(b'"') | Some(b'r') => literal.string().is_ok(),
+            Some(b'\'') => literal.char().is_ok(),
+            Some(_) => false,
+        };
+
+        if is_literal {
+            Some(self.bytes.len() - literal.bytes.len())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the id of the included file being parsed, if any.
+    pub fn file(&self) -> Option<FileId> {
+        self.file
+    }
+
+    /// Parses an `include!("path")` directive if `include` is enabled and
+    /// one follows, returning its path.
+    pub fn include(&mut self) -> Result<Option<String>> {
+        if !self.exts.contains(Extensions::INCLUDE) || !self.consume("include!") {
+            return Ok(None);
+        }
+
+        self.skip_ws()?;
+        if !self.consume("(") {
+            return self.err(ErrorCode::ExpectedInclude);
+        }
+        self.skip_ws()?;
+
+        let path = match self.string()? {
+            ParsedStr::Allocated(s) => s,
+            ParsedStr::Slice(s) => s.to_owned(),
+        };
+
+        self.skip_ws()?;
+        if !self.consume(")") {
+            return self.err(ErrorCode::ExpectedInclude);
+        }
+
+        Ok(Some(path))
+    }
+
+    /// Returns the path of each `include!` directive in the rest of the
+    /// input, with the position it is found at. Directives in literals
+    /// and comments are skipped.
+    pub fn includes(mut self) -> Result<Vec<(String, Bytes<'a>)>> {
+        let mut includes = Vec::new();
+
+        loop {
+            self.skip_ws()?;
+
+            let start = self;
+            if self.peek().is_none() {
+                return Ok(includes);
+            } else if let Some(len) = self.literal_len() {
+                self.advance(len)?;
+            } else if let Some(path) = self.include()? {
+                includes.push((path, start));
+            } else if self.identifier().is_err() {
+                self.advance_single()?;
+            }
+        }
+    }
+
+    /// Returns the length of the first element of a list whose `[` was just
+    /// consumed, if a `;` follows it and makes the list a repetition.
+    ///
+    /// The element is skipped by matching brackets rather than parsed, so
+    /// nested lists do not look ahead over each other again.
+    pub fn repetition_len(&self) -> Result<Option<usize>> {
+        let mut bytes = *self;
+        let mut depth = 0usize;
+
+        loop {
+            bytes.skip_ws()?;
+
+            match bytes.peek() {
+                None => return Ok(None),
+                Some(b';') if depth == 0 => {
+                    return Ok(Some(self.bytes.len() - bytes.bytes.len()));
+                }
+                Some(b',' | b']' | b')' | b'}') if depth == 0 => return Ok(None),
+                Some(b'[' | b'(' | b'{') => depth += 1,
+                Some(b']' | b')' | b'}') => depth -= 1,
+                Some(_) => {}
+            }
+
+            if let Some(len) = bytes.literal_len() {
+                bytes.advance(len)?;
+            } else if bytes.identifier().is_err() {
+                bytes.advance_single()?;
+            }
+        }
+    }
+
+    /// Returns true if a `b"..."` byte string or `x"..."` hex string follows
+    /// and `byte_strings` is enabled.
+    pub fn check_byte_buf_prefix(&self) -> bool {
+        self.exts.contains(Extensions::BYTE_STRINGS)
+            && (self.test_for("b\"") || self.test_for("x\""))
+    }
+
+    /// Returns true if a `b'x'` byte literal follows and `byte_strings`
+    /// is enabled.
+    pub fn check_byte_literal(&self) -> bool {
+        self.exts.contains(Extensions::BYTE_STRINGS) && self.test_for("b'")
+    }
+
+    /// Parses a byte, written as an integer or, if `byte_strings` is
+    /// enabled, a `b'x'` byte literal.
+    pub fn byte(&mut self) -> Result<u8> {
+        if !self.check_byte_literal() {
+            return self.unsigned_integer();
+        }
+        let _ = self.advance(2);
+
+        let byte = match self.eat_byte()? {
+            b'\\' => self.parse_byte_escape()?,
+            b'\'' => return self.err(ErrorCode::ExpectedChar),
+            byte if byte.is_ascii() => byte,
+            _ => return self.err(ErrorCode::ExpectedChar),
+        };
+
+        self.expect_byte(b'\'', ErrorCode::ExpectedChar)?;
+
+        Ok(byte)
+    }
+
+    fn byte_string(&mut self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+
+        loop {
+            match self
+                .eat_byte()
+                .map_err(|_| self.error(ErrorCode::ExpectedStringEnd))?
+            {
+                b'"' => break Ok(bytes),
+                b'\\' if self.skip_line_continuation() => {}
+                b'\\' => bytes.push(self.parse_byte_escape()?),
+                byte => bytes.push(byte),
+            }
+        }
+    }
+
+    fn hex_string(&mut self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+
+        while !self.consume("\"") {
+            bytes.push(self.decode_ascii_escape()?);
+        }
+
+        Ok(bytes)
+    }
+
+    fn byte_list(&mut self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+
+        self.skip_ws()?;
+        loop {
+            if self.consume("]") {
+                break Ok(bytes);
+            }
+
+            bytes.push(self.byte()?);
+            self.expect_number_suffix("u8")?;
+
+            if !self.comma()? {
+                break if self.consume("]") {
+                    Ok(bytes)
+                } else {
+                    self.err(ErrorCode::ExpectedArrayEnd)
+                };
+            }
+        }
+    }
+
+    fn parse_byte_escape(&mut self) -> Result<u8> {
+        match self.eat_byte()? {
+            b'\'' => Ok(b'\''),
+            b'"' => Ok(b'"'),
+            b'\\' => Ok(b'\\'),
+            b'n' => Ok(b'\n'),
+            b'r' => Ok(b'\r'),
+            b't' => Ok(b'\t'),
+            b'0' => Ok(b'\0'),
+            b'x' => self.decode_ascii_escape(),
+            _ => self.err(ErrorCode::InvalidEscape("Unknown escape character")),
+        }
+    }
+
     fn test_for(&self, s: &str) -> bool {
         s.bytes()
             .enumerate()
@@ -687,6 +1485,23 @@ impl<'a> Bytes<'a> {
         }
     }
 
+    /// Skips a line break following a `\\` in a string, together with the
+    /// whitespace at the start of the next line.
+    fn skip_line_continuation(&mut self) -> bool {
+        if !self.test_for("\n") && !self.test_for("\r\n") {
+            return false;
+        }
+
+        let whitespace = self
+            .bytes
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count();
+        let _ = self.advance(whitespace);
+
+        true
+    }
+
     fn parse_escape(&mut self) -> Result<char> {
         let c = match self.eat_byte()? {
             b'\'' => '\'',
@@ -695,6 +1510,7 @@ impl<'a> Bytes<'a> {
             b'n' => '\n',
             b'r' => '\r',
             b't' => '\t',
+            b'0' => '\0',
             b'x' => self.decode_ascii_escape()? as char,
             b'u' => {
                 self.expect_byte(b'{', ErrorCode::InvalidEscape("Missing {"))?;
@@ -781,6 +1597,64 @@ impl<'a> Bytes<'a> {
     }
 }
 
+/// Extracts the comments from `gap`, input which has already been parsed,
+/// skipping the string, byte string and char literals in it. Each comment
+/// is paired with whether a newline precedes it in `gap`.
+pub fn comments(mut gap: &[u8], exts: Extensions) -> Vec<(bool, String)> {
+    let mut comments = Vec::new();
+    let mut own_line = false;
+
+    while let Some(&b) = gap.first() {
+        let literal = Bytes {
+            exts,
+            bytes: gap,
+            column: 1,
+            line: 1,
+            file: None,
+        };
+
+        if let Some(len) = literal.literal_len() {
+            gap = &gap[len..];
+        } else if gap.starts_with(b"//") {
+            let len = gap.iter().take_while(|&&b| b != b'\n').count();
+            let text = String::from_utf8_lossy(&gap[2..len]);
+
+            comments.push((own_line, text.trim().to_owned()));
+            gap = &gap[len..];
+        } else if gap.starts_with(b"/*") {
+            let mut level = 0;
+            let mut len = 0;
+
+            while len < gap.len() {
+                if gap[len..].starts_with(b"/*") {
+                    level += 1;
+                    len += 2;
+                } else if gap[len..].starts_with(b"*/") {
+                    level -= 1;
+                    len += 2;
+
+                    if level == 0 {
+                        break;
+                    }
+                } else {
+                    len += 1;
+                }
+            }
+
+            let end = if level == 0 { len - 2 } else { len };
+            let text = String::from_utf8_lossy(&gap[2..end]);
+
+            comments.push((own_line, text.trim().to_owned()));
+            gap = &gap[len..];
+        } else {
+            own_line |= b == b'\n';
+            gap = &gap[1..];
+        }
+    }
+
+    comments
+}
+
 pub trait Num {
     fn from_u8(x: u8) -> Self;
 
@@ -831,6 +1705,32 @@ macro_rules! impl_num {
                 }
             }
         }
+
+        impl Operand for $ty {
+            fn apply(self, op: BinOp, rhs: Self) -> std::result::Result<Self, ErrorCode> {
+                if rhs == 0 && (op == BinOp::Div || op == BinOp::Rem) {
+                    return Err(ErrorCode::InvalidExpression("division by zero"));
+                }
+
+                // Shifting out bits overflows, like a multiplication would
+                let shift = u32::try_from(rhs).ok();
+                let res = match op {
+                    BinOp::Or => Some(self | rhs),
+                    BinOp::Xor => Some(self ^ rhs),
+                    BinOp::And => Some(self & rhs),
+                    BinOp::Shl => shift
+                        .and_then(|shift| Some(self.checked_shl(shift)?).filter(|n| n >> shift == self)),
+                    BinOp::Shr => shift.and_then(|shift| self.checked_shr(shift)),
+                    BinOp::Add => self.checked_add(rhs),
+                    BinOp::Sub => self.checked_sub(rhs),
+                    BinOp::Mul => self.checked_mul(rhs),
+                    BinOp::Div => self.checked_div(rhs),
+                    BinOp::Rem => self.checked_rem(rhs),
+                };
+
+                res.ok_or(ErrorCode::IntegerOutOfBounds)
+            }
+        }
     };
     ($($tys:ident)*) => {
         $( impl_num!($tys); )*
@@ -849,18 +1749,231 @@ pub enum ParsedStr<'a> {
 pub struct Position {
     pub col: usize,
     pub line: usize,
+    /// The included file the position is in, or `None` for the document
+    /// itself.
+    pub file: Option<FileId>,
 }
 
 impl Display for Position {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(f, "{}:{}", self.line, self.col)
+        write!(f, "{}:{}", self.line, self.col)?;
+
+        if let Some(file) = self.file {
+            write!(f, " in {}", file)?;
+        }
+
+        Ok(())
     }
 }
 
+/// Identifies an included file, whose name is looked up with
+/// `Includes::file_name`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FileId(pub(crate) usize);
+
+impl Display for FileId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "included file {}", self.0 + 1)
+    }
+}
+
+/// Returns true if `ident` can be written as an identifier without `r#`.
+pub fn is_ident(ident: &str) -> bool {
+    let mut chars = ident.chars();
+
+    chars.next().is_some_and(is_ident_first_char) && chars.all(is_ident_other_char)
+}
+
+/// Returns true if `key` can be written as a bare map key, which excludes
+/// the identifiers that are values of their own.
+pub fn is_unquoted_key(key: &str) -> bool {
+    is_ident(key) && !matches!(key, "true" | "false" | "None" | "Some" | "inf" | "NaN")
+}
+
+/// Returns `ident` as a raw identifier `r#ident`, with the characters
+/// which cannot be part of one escaped as `\u{..}`.
+pub fn raw_ident(ident: &str) -> Option<String> {
+    if ident.is_empty() {
+        return None;
+    }
+
+    let mut raw = String::from("r#");
+    for c in ident.chars() {
+        if is_ident_raw_char(c) {
+            raw.push(c);
+        } else {
+            raw.extend(c.escape_unicode());
+        }
+    }
+
+    Some(raw)
+}
+
+fn is_ident_first_char(c: char) -> bool {
+    c == '_' || unicode_ident::is_xid_start(c)
+}
+
+fn is_ident_other_char(c: char) -> bool {
+    unicode_ident::is_xid_continue(c)
+}
+
+fn is_ident_raw_char(c: char) -> bool {
+    is_ident_other_char(c) || c == '.' || c == '+' || c == '-'
+}
+
+fn is_utf8_continuation(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
+}
+
+/// Decodes the UTF-8 char at the start of `bytes`.
+fn first_char(bytes: &[u8]) -> Option<char> {
+    let len = match *bytes.first()? {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    };
+
+    from_utf8(bytes.get(..len)?).ok()?.chars().next()
+}
+
+/// Returns the number of bytes of the chars at the start of `bytes`
+/// which satisfy `predicate`.
+fn chars_len(bytes: &[u8], predicate: fn(char) -> bool) -> usize {
+    let mut len = 0;
+    while let Some(c) = bytes.get(len..).and_then(first_char) {
+        if !predicate(c) {
+            break;
+        }
+        len += c.len_utf8();
+    }
+
+    len
+}
+
+/// Returns true if `v` is a whole RFC 3339 date, time or date-time literal.
+#[cfg(feature = "datetime")]
+pub fn is_datetime(v: &str) -> bool {
+    datetime_len(v.as_bytes()) == Some(v.len())
+}
+
+/// Returns the length of the RFC 3339 `full-date`, `partial-time` or
+/// `full-time`, or `date-time` at the start of `bytes`. The offset of a
+/// date-time may be left out for a local date-time.
+#[cfg(feature = "datetime")]
+fn datetime_len(bytes: &[u8]) -> Option<usize> {
+    let len = if matches_pattern(bytes, b"dddd-dd-dd") {
+        match bytes.get(10) {
+            Some(b'T') | Some(b't') => 11 + time_len(&bytes[11..])?,
+            _ => 10,
+        }
+    } else {
+        time_len(bytes)?
+    };
+
+    // `2024-05-01-02` or `12:00:00abc` are not literals cut short
+    let rest = &bytes[len..];
+    if chars_len(rest, is_ident_other_char) > 0
+        || matches!(rest.first(), Some(b'.' | b':' | b'-' | b'+'))
+    {
+        None
+    } else {
+        Some(len)
+    }
+}
+
+/// Returns the length of the time, fraction and offset at the start of `bytes`.
+#[cfg(feature = "datetime")]
+fn time_len(bytes: &[u8]) -> Option<usize> {
+    if !matches_pattern(bytes, b"dd:dd:dd") {
+        return None;
+    }
+
+    let mut len = 8;
+    if bytes.get(len) == Some(&b'.') {
+        let fraction = bytes[len + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if fraction == 0 {
+            return None;
+        }
+        len += 1 + fraction;
+    }
+
+    match bytes.get(len) {
+        Some(b'Z') | Some(b'z') => len += 1,
+        Some(b'+') | Some(b'-') if matches_pattern(&bytes[len + 1..], b"dd:dd") => len += 6,
+        _ => {}
+    }
+
+    Some(len)
+}
+
+/// Returns true if `bytes` starts with `pattern`, where each `d` in the
+/// pattern stands for an ASCII digit.
+#[cfg(feature = "datetime")]
+fn matches_pattern(bytes: &[u8], pattern: &[u8]) -> bool {
+    bytes.len() >= pattern.len()
+        && bytes.iter().zip(pattern).all(|(&b, &p)| match p {
+            b'd' => b.is_ascii_digit(),
+            _ => b == p,
+        })
+}
+
+/// Strips the first line break of the raw string `s`, its last line if
+/// that is blank, and the common leading spaces and tabs of the other
+/// lines, the way `indoc!` does. Returns `None` if `s` does not start with
+/// a line break.
+fn unindent(s: &str) -> Option<String> {
+    let s = s.strip_prefix('\n').or_else(|| s.strip_prefix("\r\n"))?;
+
+    let is_blank = |line: &str| line.trim().is_empty();
+    let leading = |line: &str| line.len() - line.trim_start_matches(&[' ', '\t'][..]).len();
+
+    let mut lines: Vec<_> = s.split('\n').collect();
+    if lines.len() > 1 && lines.last().copied().is_some_and(is_blank) {
+        lines.pop();
+    }
+
+    let indent = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .map(|line| leading(line))
+        .min()
+        .unwrap_or(0);
+
+    let lines: Vec<_> = lines
+        .iter()
+        .map(|line| &line[leading(line).min(indent)..])
+        .collect();
+
+    Some(lines.join("\n"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn extract_comments() {
+        assert_eq!(
+            comments(
+                b", // trailing\n  /* block /* nested */ */\n//last",
+                Extensions::empty()
+            ),
+            vec![
+                (false, "trailing".to_owned()),
+                (true, "block /* nested */".to_owned()),
+                (true, "last".to_owned()),
+            ]
+        );
+        assert_eq!(
+            comments(b"\"a // b\" r#\"\"//\"# '\"' // c", Extensions::empty()),
+            vec![(false, "c".to_owned())]
+        );
+    }
+
     #[test]
     fn decode_x10() {
         let mut bytes = Bytes::new(b"10").unwrap();
@@ -0,0 +1,407 @@
+use std::fmt::{self, Write};
+
+/// Controls how the tokens of RON are written by `Serializer`.
+///
+/// Every method has a default implementation which writes the standard
+/// RON syntax, so an implementation only overrides the tokens it wants to
+/// change. Whitespace, indentation and the commas between elements are
+/// decided by the `Serializer` and its `PrettyConfig`. `DefaultFormatter`
+/// uses the defaults only.
+///
+/// With `PrettyConfig::max_width`, a value is first written on a single line
+/// to measure its width, and written again with the multi-line layout if it
+/// does not fit, discarding the first output. The formatter writes both, so
+/// the measured width matches its output. A formatter which keeps state
+/// between calls can therefore see the same value more than once.
+///
+/// # Examples
+///
+/// Writing the integers of `color` fields in hexadecimal:
+///
+/// ```
+/// use ron::ser::{Formatter, Serializer};
+/// use serde::Serialize;
+///
+/// #[derive(Default)]
+/// struct HexColors {
+///     field: String,
+/// }
+///
+/// impl Formatter for HexColors {
+///     fn write_field_name(&mut self, out: &mut String, name: &str) {
+///         self.field = name.to_owned();
+///         out.push_str(name);
+///     }
+///
+///     fn write_u128(&mut self, out: &mut String, v: u128) {
+///         if self.field == "color" {
+///             out.push_str(&format!("0x{:06X}", v));
+///         } else {
+///             out.push_str(&v.to_string());
+///         }
+///     }
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Light {
+///     color: u32,
+///     lumen: u32,
+/// }
+///
+/// let mut ser = Serializer::new(None, false).with_formatter(HexColors::default());
+/// Light { color: 0xFFAA00, lumen: 800 }.serialize(&mut ser).unwrap();
+///
+/// assert_eq!(ser.into_output_string(), "(color:0xFFAA00,lumen:800,)");
+/// ```
+pub trait Formatter {
+    /// Writes the name of a struct, enum variant or unit value.
+    fn write_identifier(&mut self, out: &mut String, name: &str) {
+        out.push_str(name);
+    }
+
+    /// Writes the name of a struct field.
+    fn write_field_name(&mut self, out: &mut String, name: &str) {
+        self.write_identifier(out, name);
+    }
+
+    /// Writes the separator between a struct field name and its value.
+    /// `pretty` is `true` within the depth limit of a `PrettyConfig`.
+    fn write_field_separator(&mut self, out: &mut String, pretty: bool) {
+        out.push_str(if pretty { ": " } else { ":" });
+    }
+
+    /// Writes the separator between a map key and its value.
+    /// `pretty` is `true` within the depth limit of a `PrettyConfig`.
+    fn write_map_key_separator(&mut self, out: &mut String, pretty: bool) {
+        out.push_str(if pretty { ": " } else { ":" });
+    }
+
+    /// Writes the start of a struct, after its optional name.
+    fn begin_struct(&mut self, out: &mut String) {
+        out.push('(');
+    }
+
+    /// Writes the end of a struct.
+    fn end_struct(&mut self, out: &mut String) {
+        out.push(')');
+    }
+
+    /// Writes the start of a tuple, tuple struct or newtype, after its optional name.
+    fn begin_tuple(&mut self, out: &mut String) {
+        out.push('(');
+    }
+
+    /// Writes the end of a tuple, tuple struct or newtype.
+    fn end_tuple(&mut self, out: &mut String) {
+        out.push(')');
+    }
+
+    /// Writes the start of a list.
+    fn begin_seq(&mut self, out: &mut String) {
+        out.push('[');
+    }
+
+    /// Writes the end of a list.
+    fn end_seq(&mut self, out: &mut String) {
+        out.push(']');
+    }
+
+    /// Writes the start of a set literal of `set_literals`.
+    fn begin_set(&mut self, out: &mut String) {
+        out.push_str("#{");
+    }
+
+    /// Writes the end of a set literal.
+    fn end_set(&mut self, out: &mut String) {
+        out.push('}');
+    }
+
+    /// Writes the start of a map.
+    fn begin_map(&mut self, out: &mut String) {
+        out.push('{');
+    }
+
+    /// Writes the end of a map.
+    fn end_map(&mut self, out: &mut String) {
+        out.push('}');
+    }
+
+    /// Writes the start of a `Some` value, unless `implicit_some` is enabled.
+    fn begin_some(&mut self, out: &mut String) {
+        out.push_str("Some(");
+    }
+
+    /// Writes the end of a `Some` value, unless `implicit_some` is enabled.
+    fn end_some(&mut self, out: &mut String) {
+        out.push(')');
+    }
+
+    /// Writes `None`.
+    fn write_none(&mut self, out: &mut String) {
+        out.push_str("None");
+    }
+
+    /// Writes the unit value `()`.
+    fn write_unit(&mut self, out: &mut String) {
+        out.push_str("()");
+    }
+
+    /// Writes a boolean.
+    fn write_bool(&mut self, out: &mut String, v: bool) {
+        out.push_str(if v { "true" } else { "false" });
+    }
+
+    /// Writes a signed integer.
+    fn write_i128(&mut self, out: &mut String, v: i128) {
+        write!(out, "{}", v).unwrap();
+    }
+
+    /// Writes an unsigned integer.
+    fn write_u128(&mut self, out: &mut String, v: u128) {
+        write!(out, "{}", v).unwrap();
+    }
+
+    /// Writes an `f32` in the `format` configured by the `PrettyConfig`.
+    fn write_f32(&mut self, out: &mut String, v: f32, format: FloatFormat) {
+        format.write_f32(out, v);
+    }
+
+    /// Writes an `f64` in the `format` configured by the `PrettyConfig`.
+    fn write_f64(&mut self, out: &mut String, v: f64, format: FloatFormat) {
+        format.write_f64(out, v);
+    }
+
+    /// Writes a quoted char in the `format` configured by the `PrettyConfig`.
+    fn write_char(&mut self, out: &mut String, v: char, format: StringFormat) {
+        format.write_char(out, v);
+    }
+
+    /// Writes a quoted string in the `format` configured by the `PrettyConfig`.
+    fn write_str(&mut self, out: &mut String, v: &str, format: StringFormat) {
+        format.write_str(out, v);
+    }
+
+    /// Writes an unquoted date/time literal of `datetime_literals`.
+    #[cfg(feature = "datetime")]
+    fn write_datetime(&mut self, out: &mut String, v: &str) {
+        out.push_str(v);
+    }
+
+    /// Writes a `b"..."` byte string.
+    fn write_byte_string(&mut self, out: &mut String, v: &[u8]) {
+        out.push_str("b\"");
+        for &byte in v {
+            match byte {
+                b'"' => out.push_str("\\\""),
+                b'\\' => out.push_str("\\\\"),
+                b'\n' => out.push_str("\\n"),
+                b'\r' => out.push_str("\\r"),
+                b'\t' => out.push_str("\\t"),
+                b'\0' => out.push_str("\\0"),
+                b' '..=b'~' => out.push(byte as char),
+                _ => write!(out, "\\x{:02x}", byte).unwrap(),
+            }
+        }
+        out.push('"');
+    }
+
+    /// Writes a `x"..."` hex string.
+    fn write_hex_string(&mut self, out: &mut String, v: &[u8]) {
+        out.push_str("x\"");
+        for byte in v {
+            write!(out, "{:02x}", byte).unwrap();
+        }
+        out.push('"');
+    }
+
+    /// Writes a line of a comment on its own line, without the line break.
+    fn write_line_comment(&mut self, out: &mut String, line: &str) {
+        out.push_str("//");
+        if !line.is_empty() {
+            out.push(' ');
+            out.push_str(line);
+        }
+    }
+
+    /// Writes a comment within a line. `text` never contains `/*` or `*/`.
+    fn write_block_comment(&mut self, out: &mut String, text: &str) {
+        out.push_str("/* ");
+        out.push_str(text);
+        out.push_str(" */");
+    }
+}
+
+/// A `Formatter` writing the standard RON syntax.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultFormatter;
+
+impl Formatter for DefaultFormatter {}
+
+/// The digits and notation floats are written with.
+///
+/// The default writes the shortest digits which read back as the same
+/// value, always with a decimal point so that the number stays a float.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FloatFormat {
+    /// Number of digits after the decimal point, taking precedence over
+    /// `significant_digits` and `scientific_threshold`
+    pub precision: Option<usize>,
+    /// Number of significant digits
+    pub significant_digits: Option<usize>,
+    /// Magnitude of the decimal exponent from which scientific notation is used
+    pub scientific_threshold: Option<usize>,
+}
+
+impl FloatFormat {
+    /// Writes an `f32` in this format.
+    pub fn write_f32(&self, out: &mut String, v: f32) {
+        self.write(out, v);
+    }
+
+    /// Writes an `f64` in this format.
+    pub fn write_f64(&self, out: &mut String, v: f64) {
+        self.write(out, v);
+    }
+
+    fn write<F: fmt::Display + fmt::LowerExp>(&self, out: &mut String, v: F) {
+        // `{:e}` gives the shortest digits which round-trip, and rounds to
+        // a number of significant digits with a precision
+        let scientific = match self.significant_digits {
+            Some(digits) => format!("{:.*e}", digits.max(1) - 1, v),
+            None => format!("{:e}", v),
+        };
+        let (mantissa, exponent) = match scientific.find('e') {
+            Some(i) => (
+                &scientific[..i],
+                scientific[i + 1..].parse::<i32>().unwrap(),
+            ),
+            // `inf`, `-inf` and `NaN`
+            None => {
+                out.push_str(&scientific);
+                return;
+            }
+        };
+
+        if let Some(precision) = self.precision {
+            let positional = format!("{:.*}", precision, v);
+            out.push_str(&positional);
+            if !positional.contains('.') {
+                out.push('.');
+            }
+            return;
+        }
+
+        match mantissa.strip_prefix('-') {
+            Some(negative) => {
+                out.push('-');
+                self.write_digits(out, negative, exponent);
+            }
+            None => self.write_digits(out, mantissa, exponent),
+        }
+    }
+
+    /// Writes the positive float `mantissa * 10^exponent`, where `mantissa`
+    /// has a single digit before its decimal point.
+    fn write_digits(&self, out: &mut String, mantissa: &str, exponent: i32) {
+        let digits = mantissa.replace('.', "");
+
+        match self.scientific_threshold {
+            Some(threshold) if exponent.unsigned_abs() as usize >= threshold => {
+                out.push_str(&digits[..1]);
+                out.push('.');
+                out.push_str(if digits.len() > 1 { &digits[1..] } else { "0" });
+                write!(out, "e{}", exponent).unwrap();
+            }
+            _ if exponent < 0 => {
+                out.push_str("0.");
+                for _ in 1..-exponent {
+                    out.push('0');
+                }
+                out.push_str(&digits);
+            }
+            _ => {
+                let point = exponent as usize + 1;
+                if digits.len() > point {
+                    out.push_str(&digits[..point]);
+                    out.push('.');
+                    out.push_str(&digits[point..]);
+                } else {
+                    out.push_str(&digits);
+                    for _ in digits.len()..point {
+                        out.push('0');
+                    }
+                    out.push_str(".0");
+                }
+            }
+        }
+    }
+}
+
+/// The quoting and escaping strings and chars are written with.
+///
+/// The default writes strings with `"` quotes, escaping them like Rust's
+/// `Debug` implementation does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StringFormat {
+    /// Write strings containing quotes, backslashes or newlines as raw strings
+    pub raw: bool,
+    /// Escape all non-ASCII characters as `\u{..}`, taking precedence over `raw`
+    pub escape_non_ascii: bool,
+    /// Strings are read with `indented_strings`, so those starting with a
+    /// line break are escaped rather than raw to keep their indentation
+    pub indented: bool,
+}
+
+impl StringFormat {
+    /// Writes a quoted string in this format.
+    pub fn write_str(&self, out: &mut String, v: &str) {
+        let needs_escapes = v.contains(&['"', '\\', '\n'][..]);
+        let can_be_raw = (v.is_ascii() || !self.escape_non_ascii)
+            && !(self.indented && (v.starts_with('\n') || v.starts_with("\r\n")));
+        if self.raw && needs_escapes && can_be_raw {
+            return write_raw_str(out, v);
+        }
+
+        out.push('"');
+        for c in v.chars() {
+            if self.escape_non_ascii && !c.is_ascii() {
+                write!(out, "\\u{{{:x}}}", c as u32).unwrap();
+            } else {
+                out.extend(c.escape_debug());
+            }
+        }
+        out.push('"');
+    }
+
+    /// Writes a quoted char in this format.
+    pub fn write_char(&self, out: &mut String, v: char) {
+        out.push('\'');
+        if v == '\\' || v == '\'' {
+            out.push('\\');
+            out.push(v);
+        } else if self.escape_non_ascii && !v.is_ascii() {
+            write!(out, "\\u{{{:x}}}", v as u32).unwrap();
+        } else {
+            out.push(v);
+        }
+        out.push('\'');
+    }
+}
+
+/// Writes `v` as a raw string, with one `#` more than the longest run of
+/// `#` following a `"` in `v`.
+fn write_raw_str(out: &mut String, v: &str) {
+    let num_hashes = v
+        .match_indices('"')
+        .map(|(i, _)| v[i + 1..].chars().take_while(|&c| c == '#').count() + 1)
+        .max()
+        .unwrap_or(0);
+    let hashes = "#".repeat(num_hashes);
+
+    out.push('r');
+    out.push_str(&hashes);
+    out.push('"');
+    out.push_str(v);
+    out.push('"');
+    out.push_str(&hashes);
+}
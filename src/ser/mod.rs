@@ -1,11 +1,16 @@
 use serde::{ser, Deserialize, Serialize};
-use std::fmt::Write;
+use std::{borrow::Cow, collections::HashMap};
 
-use crate::error::{Error, Result};
+use crate::error::{Error, ErrorCode, Position, Result};
 use crate::extensions::Extensions;
+use crate::parse;
+use crate::value::{DATETIME_NAME, SET_NAME};
 
+mod formatter;
 mod value;
 
+pub use self::formatter::{DefaultFormatter, FloatFormat, Formatter, StringFormat};
+
 /// Serializes `value` and returns it as string.
 ///
 /// This function does not generate any newlines or nice formatting;
@@ -25,14 +30,138 @@ where
     T: Serialize,
 {
     let mut s = Serializer::new(Some(config), false);
-    value.serialize(&mut s)?;
+    s.serialize_within_width(value, 0)?;
     Ok(s.output)
 }
 
+/// Returns `name` as an identifier token, written as a raw identifier
+/// `r#name` if necessary.
+fn identifier(name: &str) -> Result<Cow<'_, str>> {
+    if parse::is_ident(name) {
+        Ok(Cow::Borrowed(name))
+    } else if let Some(raw) = parse::raw_ident(name) {
+        Ok(Cow::Owned(raw))
+    } else {
+        Err(Error {
+            code: ErrorCode::InvalidIdentifier(name.to_owned()),
+            position: Position {
+                line: 0,
+                col: 0,
+                file: None,
+            },
+        })
+    }
+}
+
 /// Pretty serializer state
+#[derive(Clone)]
 struct Pretty {
     indent: usize,
     sequence_index: Vec<usize>,
+    path: Vec<String>,
+    layout: Layout,
+    /// The layouts of the values enclosing a value with a `LayoutRule`.
+    outer_layouts: Vec<Layout>,
+}
+
+/// Layout of the value currently being serialized.
+#[derive(Clone, Copy, Default)]
+struct Layout {
+    /// Whether the value is laid out on a single line.
+    inline: bool,
+    /// Whether the value is laid out on multiple lines regardless of its width.
+    expand: bool,
+    items_per_line: Option<usize>,
+    struct_names: Option<bool>,
+}
+
+impl Layout {
+    fn apply(&mut self, rule: &LayoutRule) {
+        if let Some(inline) = rule.inline {
+            self.inline = inline;
+            self.expand = !inline;
+        }
+        if rule.items_per_line.is_some() {
+            self.items_per_line = rule.items_per_line;
+        }
+        if rule.struct_names.is_some() {
+            self.struct_names = rule.struct_names;
+        }
+    }
+}
+
+/// Layout overrides for the values of a type or field, and the values nested in them.
+///
+/// # Examples
+///
+/// ```
+/// use ron::ser::{LayoutRule, PrettyConfig};
+///
+/// let my_config = PrettyConfig::new()
+///     .with_type_rule("Vec3".to_owned(), LayoutRule::new().with_inline(true))
+///     .with_field_rule("data".to_owned(), LayoutRule::new().with_items_per_line(16));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LayoutRule {
+    /// Put the value on a single line or on multiple lines
+    #[serde(default)]
+    pub inline: Option<bool>,
+    /// Number of list or tuple elements per line
+    #[serde(default)]
+    pub items_per_line: Option<usize>,
+    /// Whether struct names are written
+    #[serde(default)]
+    pub struct_names: Option<bool>,
+    /// Private field to ensure adding a field is non-breaking.
+    #[serde(skip)]
+    _future_proof: (),
+}
+
+impl LayoutRule {
+    /// Creates a `LayoutRule` which does not override anything.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Configures whether the value is put on a single line (`true`), or
+    /// every field and element gets its own line (`false`), regardless of
+    /// `max_width` and `separate_tuple_members`.
+    pub fn with_inline(mut self, inline: bool) -> Self {
+        self.inline = Some(inline);
+
+        self
+    }
+
+    /// Configures how many list or tuple elements are put on each line.
+    pub fn with_items_per_line(mut self, items_per_line: usize) -> Self {
+        self.items_per_line = Some(items_per_line);
+
+        self
+    }
+
+    /// Configures whether struct names are written, overriding the serializer.
+    pub fn with_struct_names(mut self, struct_names: bool) -> Self {
+        self.struct_names = Some(struct_names);
+
+        self
+    }
+}
+
+/// The encoding byte buffers are serialized with.
+///
+/// The deserializer accepts all of them, byte and hex strings if
+/// `byte_strings` is enabled, which the serializer then does in the header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ByteEncoding {
+    /// A base64 string, e.g. `"AGFiYw=="`
+    #[default]
+    Base64,
+    /// A byte string, e.g. `b"\x00abc"`
+    ByteString,
+    /// A hex string, e.g. `x"00616263"`
+    Hex,
+    /// A list of integers, e.g. `[0, 97, 98, 99]`
+    List,
 }
 
 /// Pretty serializer configuration.
@@ -64,8 +193,39 @@ pub struct PrettyConfig {
     /// Enumerate array items in comments
     #[serde(default = "default_enumerate_arrays")]
     pub enumerate_arrays: bool,
-    /// Enable extensions. Only configures 'implicit_some' for now.
+    /// Enable extensions. The serializer writes the `#![enable(..)]` attribute and
+    /// uses the syntax of the extensions.
     pub extensions: Extensions,
+    /// Comments to emit before the values at the given paths
+    #[serde(default)]
+    pub comments: HashMap<String, String>,
+    /// Put compound values on a single line if they fit into this width
+    #[serde(default)]
+    pub max_width: Option<usize>,
+    /// Number of digits after the decimal point of floats
+    #[serde(default)]
+    pub float_precision: Option<usize>,
+    /// Number of significant digits of floats
+    #[serde(default)]
+    pub float_significant_digits: Option<usize>,
+    /// Magnitude of the decimal exponent from which floats use scientific notation
+    #[serde(default)]
+    pub float_scientific_threshold: Option<usize>,
+    /// Encoding of byte buffers
+    #[serde(default)]
+    pub byte_encoding: ByteEncoding,
+    /// Write strings which would need escapes as raw strings
+    #[serde(default)]
+    pub raw_strings: bool,
+    /// Escape all non-ASCII characters in strings and chars
+    #[serde(default)]
+    pub escape_non_ascii: bool,
+    /// Layout rules for the values of types, by type name
+    #[serde(default)]
+    pub type_rules: HashMap<String, LayoutRule>,
+    /// Layout rules for the values of struct fields, by field name
+    #[serde(default)]
+    pub field_rules: HashMap<String, LayoutRule>,
     /// Private field to ensure adding a field is non-breaking.
     #[serde(skip)]
     _future_proof: (),
@@ -135,6 +295,125 @@ impl PrettyConfig {
 
         self
     }
+
+    /// Configures the maximum line width. A struct, tuple, list or map is
+    /// written on a single line if it fits, and broken into one line per
+    /// element otherwise. Tuples are broken up even if
+    /// `separate_tuple_members` is disabled.
+    ///
+    /// The width is only respected by `Serializer`, and a single scalar
+    /// value or deeply nested value can still exceed it.
+    ///
+    /// Default: `None`, i.e. only `depth_limit` and `separate_tuple_members`
+    /// decide the layout
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+
+        self
+    }
+
+    /// Configures floats to be written with a fixed number of digits after
+    /// the decimal point, e.g. `0.333` and `4.000` with a precision of 3.
+    /// Takes precedence over `float_significant_digits` and
+    /// `float_scientific_threshold`.
+    ///
+    /// Default: `None`, i.e. the shortest digits which read back as the same value
+    pub fn with_float_precision(mut self, float_precision: usize) -> Self {
+        self.float_precision = Some(float_precision);
+
+        self
+    }
+
+    /// Configures floats to be rounded to a number of significant digits,
+    /// e.g. `1235.0` and `0.0001235` with 4 significant digits.
+    ///
+    /// Default: `None`, i.e. the shortest digits which read back as the same value
+    pub fn with_float_significant_digits(mut self, float_significant_digits: usize) -> Self {
+        self.float_significant_digits = Some(float_significant_digits);
+
+        self
+    }
+
+    /// Configures floats to be written in scientific notation if the
+    /// magnitude of their decimal exponent is at least `float_scientific_threshold`,
+    /// e.g. `1.5e-7` and `2.0e10` with a threshold of 6.
+    ///
+    /// Default: `None`, i.e. floats are always written positionally
+    pub fn with_float_scientific_threshold(mut self, float_scientific_threshold: usize) -> Self {
+        self.float_scientific_threshold = Some(float_scientific_threshold);
+
+        self
+    }
+
+    /// Configures the encoding of byte buffers. `ByteEncoding::ByteString`
+    /// and `ByteEncoding::Hex` enable the `byte_strings` extension.
+    ///
+    /// Default: `ByteEncoding::Base64`
+    pub fn with_byte_encoding(mut self, byte_encoding: ByteEncoding) -> Self {
+        self.byte_encoding = byte_encoding;
+
+        self
+    }
+
+    /// Configures whether strings containing quotes, backslashes or newlines
+    /// are written as raw strings, e.g. `r"C:\Users"` or `r#"say "hi""#`.
+    /// Strings are escaped regardless if `escape_non_ascii` is set and they
+    /// contain non-ASCII characters, or if `indented_strings` is enabled and
+    /// they start with a line break.
+    ///
+    /// Default: `false`
+    pub fn with_raw_strings(mut self, raw_strings: bool) -> Self {
+        self.raw_strings = raw_strings;
+
+        self
+    }
+
+    /// Configures whether all non-ASCII characters in strings and chars are
+    /// escaped as `\u{..}`, so that the output is plain ASCII.
+    ///
+    /// Default: `false`
+    pub fn with_escape_non_ascii(mut self, escape_non_ascii: bool) -> Self {
+        self.escape_non_ascii = escape_non_ascii;
+
+        self
+    }
+
+    /// Adds a layout rule for the values of the struct, tuple struct,
+    /// newtype struct or enum with the type name `name`. The rule of an
+    /// enum applies to its newtype, tuple and struct variants. Rules also
+    /// apply to the values nested inside, unless these have a rule themselves.
+    ///
+    /// Default: no rules
+    pub fn with_type_rule(mut self, name: String, rule: LayoutRule) -> Self {
+        self.type_rules.insert(name, rule);
+
+        self
+    }
+
+    /// Adds a layout rule for the values of struct fields named `name`.
+    /// Rules also apply to the values nested inside, unless these have
+    /// a rule themselves.
+    ///
+    /// Default: no rules
+    pub fn with_field_rule(mut self, name: String, rule: LayoutRule) -> Self {
+        self.field_rules.insert(name, rule);
+
+        self
+    }
+
+    /// Adds a comment which is written before the value at `path`.
+    ///
+    /// A path consists of segments joined by `.`: struct field names, the
+    /// indices of sequence and tuple elements, and map keys in their compact
+    /// RON form. For example, `characters."guy".friendly` or `buildings.0`.
+    /// Multi-line layouts use `//` comments, single-line ones `/* */` comments.
+    ///
+    /// Default: no comments
+    pub fn with_comment(mut self, path: String, comment: String) -> Self {
+        self.comments.insert(path, comment);
+
+        self
+    }
 }
 
 fn default_depth_limit() -> usize {
@@ -171,6 +450,16 @@ impl Default for PrettyConfig {
             separate_tuple_members: default_separate_tuple_members(),
             enumerate_arrays: default_enumerate_arrays(),
             extensions: Extensions::default(),
+            comments: HashMap::new(),
+            max_width: None,
+            float_precision: None,
+            float_significant_digits: None,
+            float_scientific_threshold: None,
+            byte_encoding: ByteEncoding::default(),
+            raw_strings: false,
+            escape_non_ascii: false,
+            type_rules: HashMap::new(),
+            field_rules: HashMap::new(),
             _future_proof: (),
         }
     }
@@ -185,6 +474,29 @@ pub struct Serializer {
     pretty: Option<(PrettyConfig, Pretty)>,
     struct_names: bool,
     is_empty: Option<bool>,
+    formatter: Box<dyn Formatter>,
+    /// Output length at the start of the current map key, used to write a
+    /// string key bare if `unquoted_map_keys` is enabled
+    map_key_start: Option<usize>,
+    /// Output length at the start of a newtype variant's value, whose
+    /// parentheses a struct or tuple shares if `unwrap_variant_newtypes`
+    /// is enabled
+    newtype_variant_start: Option<usize>,
+    /// Output length at the start of the document, where a struct is
+    /// written without parentheses if `implicit_outer_struct` is enabled
+    outer_struct_start: Option<usize>,
+    /// Output length at the start of a `Value::DateTime`'s string, which is
+    /// written unquoted if `datetime_literals` is enabled
+    #[cfg(feature = "datetime")]
+    datetime_start: Option<usize>,
+    /// Output length at the start of a `Value::Set`'s sequence, which is
+    /// written as a set literal if `set_literals` is enabled
+    set_start: Option<usize>,
+    /// Whether each open sequence is a set literal
+    open_sets: Vec<bool>,
+    /// Whether each open struct is the outer struct written without
+    /// parentheses
+    open_structs: Vec<bool>,
 }
 
 impl Serializer {
@@ -192,15 +504,30 @@ impl Serializer {
     ///
     /// Most of the time you can just use `to_string` or `to_string_pretty`.
     pub fn new(config: Option<PrettyConfig>, struct_names: bool) -> Self {
+        // Byte and hex strings can only be read back with `byte_strings`
+        let config = config.map(|mut conf| {
+            if let ByteEncoding::ByteString | ByteEncoding::Hex = conf.byte_encoding {
+                conf.extensions |= Extensions::BYTE_STRINGS;
+            }
+
+            conf
+        });
         let initial_output = if let Some(conf) = &config {
-            if conf.extensions.contains(Extensions::IMPLICIT_SOME) {
-                "#![enable(implicit_some)]".to_string() + &conf.new_line
-            } else {
+            let idents = conf.extensions.idents();
+            if idents.is_empty() {
                 String::new()
+            } else {
+                format!("#![enable({})]", idents.join(", ")) + &conf.new_line
             }
         } else {
             String::new()
         };
+        let outer_struct_start = match config {
+            Some(ref conf) if conf.extensions.contains(Extensions::IMPLICIT_OUTER_STRUCT) => {
+                Some(initial_output.len())
+            }
+            _ => None,
+        };
         Serializer {
             output: initial_output,
             pretty: config.map(|conf| {
@@ -209,30 +536,68 @@ impl Serializer {
                     Pretty {
                         indent: 0,
                         sequence_index: Vec::new(),
+                        path: Vec::new(),
+                        layout: Layout::default(),
+                        outer_layouts: Vec::new(),
                     },
                 )
             }),
             struct_names,
             is_empty: None,
+            formatter: Box::new(DefaultFormatter),
+            map_key_start: None,
+            newtype_variant_start: None,
+            outer_struct_start,
+            #[cfg(feature = "datetime")]
+            datetime_start: None,
+            set_start: None,
+            open_sets: Vec::new(),
+            open_structs: Vec::new(),
         }
     }
 
+    /// Uses `formatter` to write the tokens of RON, instead of `DefaultFormatter`.
+    pub fn with_formatter<F: Formatter + 'static>(mut self, formatter: F) -> Self {
+        self.formatter = Box::new(formatter);
+
+        self
+    }
+
     /// Consumes `self` and returns the built `String`.
     pub fn into_output_string(self) -> String {
         self.output
     }
 
-    fn is_pretty(&self) -> bool {
+    pub(crate) fn is_pretty(&self) -> bool {
         match self.pretty {
             Some((ref config, ref pretty)) => pretty.indent < config.depth_limit,
             None => false,
         }
     }
 
-    fn separate_tuple_members(&self) -> bool {
-        self.pretty
-            .as_ref()
-            .map_or(false, |&(ref config, _)| config.separate_tuple_members)
+    /// Whether fields and elements are put on separate lines.
+    fn is_multi_line(&self) -> bool {
+        match self.pretty {
+            Some((ref config, ref pretty)) => {
+                !pretty.layout.inline && pretty.indent < config.depth_limit
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn separate_tuple_members(&self) -> bool {
+        match self.pretty {
+            Some((ref config, ref pretty)) => {
+                let layout = pretty.layout;
+
+                !layout.inline
+                    && (layout.expand
+                        || layout.items_per_line.is_some()
+                        || config.separate_tuple_members
+                        || config.max_width.is_some())
+            }
+            None => false,
+        }
     }
 
     fn extensions(&self) -> Extensions {
@@ -244,46 +609,547 @@ impl Serializer {
     fn start_indent(&mut self) {
         if let Some((ref config, ref mut pretty)) = self.pretty {
             pretty.indent += 1;
-            if pretty.indent < config.depth_limit {
+            if !pretty.layout.inline && pretty.indent < config.depth_limit {
+                let is_empty = self.is_empty.unwrap_or(false);
+
+                if !is_empty {
+                    self.output += &config.new_line;
+                }
+            }
+        }
+    }
+
+    fn indent(&mut self) {
+        if let Some((ref config, ref pretty)) = self.pretty {
+            if !pretty.layout.inline && pretty.indent < config.depth_limit {
+                self.output
+                    .extend((0..pretty.indent).map(|_| config.indentor.as_str()));
+            }
+        }
+    }
+
+    fn end_indent(&mut self) {
+        if !self.is_multi_line() {
+            self.trim_separator();
+        }
+
+        if let Some((ref config, ref mut pretty)) = self.pretty {
+            if !pretty.layout.inline && pretty.indent < config.depth_limit {
                 let is_empty = self.is_empty.unwrap_or(false);
 
-                if !is_empty {
-                    self.output += &config.new_line;
-                }
-            }
+                if !is_empty {
+                    self.output
+                        .extend((1..pretty.indent).map(|_| config.indentor.as_str()));
+                }
+            }
+            pretty.indent -= 1;
+
+            self.is_empty = None;
+        }
+    }
+
+    /// Ends a field or element, continuing on a new line or the same line.
+    fn new_line(&mut self) {
+        if let Some((ref config, ref pretty)) = self.pretty {
+            if pretty.indent < config.depth_limit {
+                if pretty.layout.inline {
+                    self.output += " ";
+                } else {
+                    self.output += &config.new_line;
+                }
+            }
+        }
+    }
+
+    /// Removes the separator after the last element of a single-line value.
+    fn trim_separator(&mut self) {
+        if self.is_pretty() && self.output.ends_with(", ") {
+            self.output.pop();
+            self.output.pop();
+        }
+    }
+
+    /// Serializes `value` on a single line if it fits into `max_width`
+    /// together with the `trailing` characters following it, and
+    /// with the regular layout otherwise.
+    ///
+    /// The single line trial goes through the formatter as well, see the
+    /// documentation of `Formatter`.
+    fn serialize_within_width<T>(&mut self, value: &T, trailing: usize) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let max_width = match self.pretty {
+            Some((ref config, ref pretty)) if !pretty.layout.inline && !pretty.layout.expand => {
+                config.max_width
+            }
+            _ => None,
+        };
+        // An outer struct without parentheses is always laid out on multiple lines
+        let max_width = max_width.filter(|_| self.outer_struct_start != Some(self.output.len()));
+
+        if let (Some(max_width), true) = (max_width, self.is_multi_line()) {
+            let start = self.output.len();
+            let pretty = self.pretty.as_ref().map(|(_, pretty)| pretty.clone());
+            let is_empty = self.is_empty;
+            let open_sets = self.open_sets.len();
+            let open_structs = self.open_structs.len();
+
+            self.set_inline(true);
+            match value.serialize(&mut *self) {
+                Ok(()) => {
+                    self.set_inline(false);
+
+                    let line_start = self.output[..start].rfind('\n').map_or(0, |i| i + 1);
+                    let line = &self.output[line_start..];
+                    if !line[start - line_start..].contains('\n')
+                        && line.chars().count() + trailing <= max_width
+                    {
+                        return Ok(());
+                    }
+                }
+                // A comment which cannot be written inline does not fit on a single line
+                Err(Error {
+                    code: ErrorCode::UnrepresentableComment,
+                    ..
+                }) => {
+                    if let (Some((_, ref mut current)), Some(pretty)) = (&mut self.pretty, pretty) {
+                        *current = pretty;
+                    }
+                    self.is_empty = is_empty;
+                    self.open_sets.truncate(open_sets);
+                    self.open_structs.truncate(open_structs);
+                }
+                Err(e) => return Err(e),
+            }
+
+            self.output.truncate(start);
+        }
+
+        value.serialize(&mut *self)
+    }
+
+    fn set_inline(&mut self, inline: bool) {
+        if let Some((_, ref mut pretty)) = self.pretty {
+            pretty.layout.inline = inline;
+        }
+    }
+
+    /// Applies the layout rule for the type `name` until `pop_layout_rule`.
+    fn push_type_rule(&mut self, name: &str) {
+        if let Some((ref config, ref mut pretty)) = self.pretty {
+            pretty.outer_layouts.push(pretty.layout);
+            if let Some(rule) = config.type_rules.get(name) {
+                pretty.layout.apply(rule);
+            }
+        }
+    }
+
+    /// Applies the layout rule for the field `name` until `pop_layout_rule`.
+    fn push_field_rule(&mut self, name: &str) {
+        if let Some((ref config, ref mut pretty)) = self.pretty {
+            pretty.outer_layouts.push(pretty.layout);
+            if let Some(rule) = config.field_rules.get(name) {
+                pretty.layout.apply(rule);
+            }
+        }
+    }
+
+    fn pop_layout_rule(&mut self) {
+        if let Some((_, ref mut pretty)) = self.pretty {
+            pretty.layout = pretty.outer_layouts.pop().unwrap();
+        }
+    }
+
+    fn struct_names(&self) -> bool {
+        match self.pretty {
+            Some((_, ref pretty)) => pretty.layout.struct_names.unwrap_or(self.struct_names),
+            None => self.struct_names,
+        }
+    }
+
+    /// The number of elements per line, if elements share lines.
+    fn items_per_line(&self) -> Option<usize> {
+        match self.pretty {
+            Some((_, ref pretty)) if self.is_multi_line() => pretty.layout.items_per_line,
+            _ => None,
+        }
+    }
+
+    /// The column of the next element of a list or tuple, and the number of columns.
+    fn element_column(&self) -> (usize, usize) {
+        let columns = self.items_per_line().map_or(1, |n| n.max(1));
+
+        (self.element_index() % columns, columns)
+    }
+
+    /// Whether the next element of a list or tuple starts a new line.
+    pub(crate) fn is_line_start(&self) -> bool {
+        self.element_column().0 == 0
+    }
+
+    /// Whether the next element of a list or tuple ends its line.
+    fn is_line_end(&self) -> bool {
+        let (column, columns) = self.element_column();
+
+        column + 1 == columns
+    }
+
+    /// Ends the current line of elements if it is only partially filled.
+    fn end_partial_line(&mut self) {
+        if self.items_per_line().is_some() && self.output.ends_with(", ") {
+            self.output.pop();
+            self.new_line();
+        }
+    }
+
+    fn string_format(&self) -> StringFormat {
+        match self.pretty {
+            Some((ref config, _)) => StringFormat {
+                raw: config.raw_strings,
+                escape_non_ascii: config.escape_non_ascii,
+                indented: config.extensions.contains(Extensions::INDENTED_STRINGS),
+            },
+            None => StringFormat::default(),
+        }
+    }
+
+    fn float_format(&self) -> FloatFormat {
+        match self.pretty {
+            Some((ref config, _)) => FloatFormat {
+                precision: config.float_precision,
+                significant_digits: config.float_significant_digits,
+                scientific_threshold: config.float_scientific_threshold,
+            },
+            None => FloatFormat::default(),
+        }
+    }
+
+    fn has_comments(&self) -> bool {
+        match self.pretty {
+            Some((ref config, _)) => !config.comments.is_empty(),
+            None => false,
+        }
+    }
+
+    fn element_index(&self) -> usize {
+        match self.pretty {
+            Some((_, ref pretty)) => pretty.sequence_index.last().cloned().unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    /// Descends into `segment` of the comment path and writes
+    /// the comment configured for the resulting path, if any.
+    fn push_path(&mut self, segment: String, own_line: bool) -> Result<()> {
+        let comment = match self.pretty {
+            Some((ref config, ref mut pretty)) => {
+                pretty.path.push(segment);
+                config.comments.get(&pretty.path.join(".")).cloned()
+            }
+            None => None,
+        };
+
+        match comment {
+            Some(comment) => self.write_comment(&comment, own_line),
+            None => Ok(()),
+        }
+    }
+
+    fn pop_path(&mut self) {
+        if let Some((_, ref mut pretty)) = self.pretty {
+            pretty.path.pop();
+        }
+    }
+
+    /// Writes `name` as an identifier, or as a raw identifier `r#name`
+    /// if it is not a valid identifier by itself.
+    pub(crate) fn write_identifier(&mut self, name: &str) -> Result<()> {
+        let ident = identifier(name)?;
+        self.formatter.write_identifier(&mut self.output, &ident);
+
+        Ok(())
+    }
+
+    /// Writes the suffix of a number of type `ty` if `number_suffixes` is
+    /// enabled. `inf` and `NaN` cannot have a suffix, so floats are only
+    /// given one if they are finite.
+    fn write_number_suffix(&mut self, ty: &str) {
+        if self.extensions().contains(Extensions::NUMBER_SUFFIXES) {
+            self.output += ty;
+        }
+    }
+
+    /// Writes the name of an enum variant, prefixed by `Type::` if
+    /// `variant_paths` is enabled.
+    fn write_variant(&mut self, name: &str, variant: &str) -> Result<()> {
+        if self.extensions().contains(Extensions::VARIANT_PATHS) {
+            self.write_identifier(name)?;
+            self.output += "::";
+        }
+
+        self.write_identifier(variant)
+    }
+
+    /// Writes a comment, either as `//` lines of its own or as an inline `/* */` block.
+    pub(crate) fn write_comment(&mut self, text: &str, own_line: bool) -> Result<()> {
+        if own_line && self.is_multi_line() {
+            for line in text.lines() {
+                self.indent();
+                self.formatter.write_line_comment(&mut self.output, line);
+                self.new_line();
+            }
+        } else {
+            if text.contains("/*") || text.contains("*/") {
+                return Err(Error {
+                    code: ErrorCode::UnrepresentableComment,
+                    position: Position {
+                        line: 0,
+                        col: 0,
+                        file: None,
+                    },
+                });
+            }
+
+            self.formatter.write_block_comment(&mut self.output, text);
+            if self.is_pretty() {
+                self.output += " ";
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn start_some(&mut self) {
+        if !self.extensions().contains(Extensions::IMPLICIT_SOME) {
+            self.formatter.begin_some(&mut self.output);
+        }
+    }
+
+    pub(crate) fn end_some(&mut self) {
+        if !self.extensions().contains(Extensions::IMPLICIT_SOME) {
+            self.formatter.end_some(&mut self.output);
+        }
+    }
+
+    pub(crate) fn start_seq(&mut self, len: Option<usize>) {
+        let is_set = self.set_start.take() == Some(self.output.len())
+            && self.extensions().contains(Extensions::SET_LITERALS);
+        if is_set {
+            self.formatter.begin_set(&mut self.output);
+        } else {
+            self.formatter.begin_seq(&mut self.output);
+        }
+        self.open_sets.push(is_set);
+
+        if let Some(len) = len {
+            self.is_empty = Some(len == 0);
+        }
+
+        self.start_indent();
+
+        if let Some((_, ref mut pretty)) = self.pretty {
+            pretty.sequence_index.push(0);
+        }
+    }
+
+    pub(crate) fn start_seq_element(&mut self) {
+        if self.is_line_start() {
+            self.indent();
+        }
+    }
+
+    pub(crate) fn end_seq_element(&mut self) {
+        self.output += ",";
+
+        let (column, _) = self.element_column();
+        let is_line_end = self.is_line_end();
+
+        if let Some((ref config, ref mut pretty)) = self.pretty {
+            let index = pretty.sequence_index.last_mut().unwrap();
+            if !pretty.layout.inline
+                && pretty.indent < config.depth_limit
+                && config.enumerate_arrays
+                && is_line_end
+            {
+                assert!(config.new_line.contains('\n'));
+                //TODO: when /**/ comments are supported, prepend the index
+                // to an element instead of appending it.
+                self.formatter
+                    .write_line_comment(&mut self.output, &format!("[{}]", *index - column));
+            }
+            *index += 1;
+        }
+
+        if is_line_end {
+            self.new_line();
+        } else {
+            self.output += " ";
+        }
+    }
+
+    pub(crate) fn end_seq(&mut self) {
+        self.end_partial_line();
+        self.end_indent();
+
+        if let Some((_, ref mut pretty)) = self.pretty {
+            pretty.sequence_index.pop();
+        }
+
+        if self.open_sets.pop() == Some(true) {
+            self.formatter.end_set(&mut self.output);
+        } else {
+            self.formatter.end_seq(&mut self.output);
+        }
+    }
+
+    /// Returns `true` at the start of a newtype variant's value, where a
+    /// struct or tuple is written without its name.
+    fn is_newtype_variant_start(&self) -> bool {
+        self.newtype_variant_start == Some(self.output.len())
+    }
+
+    pub(crate) fn start_tuple(&mut self, name: Option<&str>, len: Option<usize>) -> Result<()> {
+        if self.is_newtype_variant_start() {
+            self.newtype_variant_start = None;
+        } else if let Some(name) = name {
+            self.write_identifier(name)?;
+        }
+        self.formatter.begin_tuple(&mut self.output);
+
+        if self.separate_tuple_members() {
+            self.is_empty = len.map(|len| len == 0);
+
+            self.start_indent();
+        }
+
+        if let Some((_, ref mut pretty)) = self.pretty {
+            pretty.sequence_index.push(0);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn start_tuple_element(&mut self) {
+        if self.separate_tuple_members() && self.is_line_start() {
+            self.indent();
+        }
+    }
+
+    pub(crate) fn end_tuple_element(&mut self) {
+        self.output += ",";
+
+        let separate_tuple_members = self.separate_tuple_members();
+        let is_line_end = self.is_line_end();
+        if let Some((ref config, ref mut pretty)) = self.pretty {
+            *pretty.sequence_index.last_mut().unwrap() += 1;
+            if pretty.indent < config.depth_limit {
+                self.output += if separate_tuple_members && is_line_end {
+                    &config.new_line
+                } else {
+                    " "
+                };
+            }
+        }
+    }
+
+    pub(crate) fn end_tuple(&mut self) {
+        if let Some((_, ref mut pretty)) = self.pretty {
+            pretty.sequence_index.pop();
+        }
+
+        if self.separate_tuple_members() {
+            self.end_partial_line();
+            self.end_indent();
+        } else {
+            self.trim_separator();
+        }
+
+        self.formatter.end_tuple(&mut self.output);
+    }
+
+    pub(crate) fn start_map(&mut self, len: Option<usize>) {
+        self.formatter.begin_map(&mut self.output);
+
+        if let Some(len) = len {
+            self.is_empty = Some(len == 0);
+        }
+
+        self.start_indent();
+    }
+
+    pub(crate) fn start_map_key(&mut self) {
+        self.indent();
+        self.map_key_start = Some(self.output.len());
+    }
+
+    pub(crate) fn start_map_value(&mut self) {
+        self.map_key_start = None;
+        let pretty = self.is_pretty();
+        self.formatter
+            .write_map_key_separator(&mut self.output, pretty);
+    }
+
+    pub(crate) fn end_map_value(&mut self) {
+        self.output += ",";
+        self.new_line();
+    }
+
+    pub(crate) fn end_map(&mut self) {
+        self.end_indent();
+
+        self.formatter.end_map(&mut self.output);
+    }
+
+    pub(crate) fn start_struct(&mut self, name: Option<&str>, len: Option<usize>) -> Result<()> {
+        let is_outer = self.outer_struct_start == Some(self.output.len());
+        self.open_structs.push(is_outer);
+
+        if is_outer {
+            // The fields of the outer struct are written without indentation
+            self.outer_struct_start = None;
+
+            return Ok(());
         }
-    }
 
-    fn indent(&mut self) {
-        if let Some((ref config, ref pretty)) = self.pretty {
-            if pretty.indent < config.depth_limit {
-                self.output
-                    .extend((0..pretty.indent).map(|_| config.indentor.as_str()));
-            }
+        if self.is_newtype_variant_start() {
+            self.newtype_variant_start = None;
+        } else if let Some(name) = name {
+            self.write_identifier(name)?;
         }
+        self.formatter.begin_struct(&mut self.output);
+
+        self.is_empty = len.map(|len| len == 0);
+        self.start_indent();
+
+        Ok(())
     }
 
-    fn end_indent(&mut self) {
-        if let Some((ref config, ref mut pretty)) = self.pretty {
-            if pretty.indent < config.depth_limit {
-                let is_empty = self.is_empty.unwrap_or(false);
+    pub(crate) fn start_struct_field(&mut self, key: &str) -> Result<()> {
+        self.indent();
 
-                if !is_empty {
-                    self.output
-                        .extend((1..pretty.indent).map(|_| config.indentor.as_str()));
-                }
-            }
-            pretty.indent -= 1;
+        let pretty = self.is_pretty();
+        let key = identifier(key)?;
+        self.formatter.write_field_name(&mut self.output, &key);
+        self.formatter
+            .write_field_separator(&mut self.output, pretty);
 
-            self.is_empty = None;
-        }
+        Ok(())
     }
 
-    fn serialize_escaped_str(&mut self, value: &str) {
-        let value = value.chars().flat_map(|c| c.escape_debug());
-        self.output += "\"";
-        self.output.extend(value);
-        self.output += "\"";
+    pub(crate) fn end_struct_field(&mut self) {
+        self.output += ",";
+        self.new_line();
+    }
+
+    pub(crate) fn end_struct(&mut self) {
+        if self.open_structs.pop() == Some(true) {
+            return;
+        }
+
+        self.end_indent();
+
+        self.formatter.end_struct(&mut self.output);
     }
 }
 
@@ -299,85 +1165,152 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     type SerializeTupleVariant = Self;
 
     fn serialize_bool(self, v: bool) -> Result<()> {
-        self.output += if v { "true" } else { "false" };
+        self.formatter.write_bool(&mut self.output, v);
         Ok(())
     }
 
     fn serialize_i8(self, v: i8) -> Result<()> {
-        self.serialize_i128(v as i128)
+        self.formatter.write_i128(&mut self.output, v as i128);
+        self.write_number_suffix("i8");
+        Ok(())
     }
 
     fn serialize_i16(self, v: i16) -> Result<()> {
-        self.serialize_i128(v as i128)
+        self.formatter.write_i128(&mut self.output, v as i128);
+        self.write_number_suffix("i16");
+        Ok(())
     }
 
     fn serialize_i32(self, v: i32) -> Result<()> {
-        self.serialize_i128(v as i128)
+        self.formatter.write_i128(&mut self.output, v as i128);
+        self.write_number_suffix("i32");
+        Ok(())
     }
 
     fn serialize_i64(self, v: i64) -> Result<()> {
-        self.serialize_i128(v as i128)
+        self.formatter.write_i128(&mut self.output, v as i128);
+        self.write_number_suffix("i64");
+        Ok(())
     }
 
     fn serialize_i128(self, v: i128) -> Result<()> {
-        // TODO optimize
-        self.output += &v.to_string();
+        self.formatter.write_i128(&mut self.output, v);
+        self.write_number_suffix("i128");
         Ok(())
     }
 
     fn serialize_u8(self, v: u8) -> Result<()> {
-        self.serialize_u128(v as u128)
+        self.formatter.write_u128(&mut self.output, v as u128);
+        self.write_number_suffix("u8");
+        Ok(())
     }
 
     fn serialize_u16(self, v: u16) -> Result<()> {
-        self.serialize_u128(v as u128)
+        self.formatter.write_u128(&mut self.output, v as u128);
+        self.write_number_suffix("u16");
+        Ok(())
     }
 
     fn serialize_u32(self, v: u32) -> Result<()> {
-        self.serialize_u128(v as u128)
+        self.formatter.write_u128(&mut self.output, v as u128);
+        self.write_number_suffix("u32");
+        Ok(())
     }
 
     fn serialize_u64(self, v: u64) -> Result<()> {
-        self.serialize_u128(v as u128)
+        self.formatter.write_u128(&mut self.output, v as u128);
+        self.write_number_suffix("u64");
+        Ok(())
     }
 
     fn serialize_u128(self, v: u128) -> Result<()> {
-        self.output += &v.to_string();
+        self.formatter.write_u128(&mut self.output, v);
+        self.write_number_suffix("u128");
         Ok(())
     }
 
     fn serialize_f32(self, v: f32) -> Result<()> {
-        self.output += &v.to_string();
+        let format = self.float_format();
+        self.formatter.write_f32(&mut self.output, v, format);
+        if v.is_finite() {
+            self.write_number_suffix("f32");
+        }
         Ok(())
     }
 
     fn serialize_f64(self, v: f64) -> Result<()> {
-        self.output += &v.to_string();
+        let format = self.float_format();
+        self.formatter.write_f64(&mut self.output, v, format);
+        if v.is_finite() {
+            self.write_number_suffix("f64");
+        }
         Ok(())
     }
 
     fn serialize_char(self, v: char) -> Result<()> {
-        self.output += "'";
-        if v == '\\' || v == '\'' {
-            self.output.push('\\');
-        }
-        self.output.push(v);
-        self.output += "'";
+        let format = self.string_format();
+        self.formatter.write_char(&mut self.output, v, format);
         Ok(())
     }
 
     fn serialize_str(self, v: &str) -> Result<()> {
-        self.serialize_escaped_str(v);
+        if self.map_key_start.take() == Some(self.output.len())
+            && self.extensions().contains(Extensions::UNQUOTED_MAP_KEYS)
+            && parse::is_unquoted_key(v)
+        {
+            self.formatter.write_identifier(&mut self.output, v);
+
+            return Ok(());
+        }
+
+        #[cfg(feature = "datetime")]
+        {
+            if self.datetime_start.take() == Some(self.output.len())
+                && self.extensions().contains(Extensions::DATETIME_LITERALS)
+                && parse::is_datetime(v)
+            {
+                self.formatter.write_datetime(&mut self.output, v);
+
+                return Ok(());
+            }
+        }
+
+        let format = self.string_format();
+        self.formatter.write_str(&mut self.output, v, format);
 
         Ok(())
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<()> {
-        self.serialize_str(base64::encode(v).as_str())
+        let byte_encoding = match self.pretty {
+            Some((ref config, _)) => config.byte_encoding,
+            None => ByteEncoding::default(),
+        };
+
+        match byte_encoding {
+            ByteEncoding::Base64 => self.serialize_str(base64::encode(v).as_str()),
+            ByteEncoding::ByteString => {
+                self.formatter.write_byte_string(&mut self.output, v);
+
+                Ok(())
+            }
+            ByteEncoding::Hex => {
+                self.formatter.write_hex_string(&mut self.output, v);
+
+                Ok(())
+            }
+            ByteEncoding::List => {
+                let mut seq = self.serialize_seq(Some(v.len()))?;
+                for byte in v {
+                    ser::SerializeSeq::serialize_element(&mut seq, byte)?;
+                }
+                ser::SerializeSeq::end(seq)
+            }
+        }
     }
 
     fn serialize_none(self) -> Result<()> {
-        self.output += "None";
+        self.formatter.write_none(&mut self.output);
 
         Ok(())
     }
@@ -386,27 +1319,23 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        let implicit_some = self.extensions().contains(Extensions::IMPLICIT_SOME);
-        if !implicit_some {
-            self.output += "Some(";
-        }
+        self.map_key_start = None;
+        self.start_some();
         value.serialize(&mut *self)?;
-        if !implicit_some {
-            self.output += ")";
-        }
+        self.end_some();
 
         Ok(())
     }
 
     fn serialize_unit(self) -> Result<()> {
-        self.output += "()";
+        self.formatter.write_unit(&mut self.output);
 
         Ok(())
     }
 
     fn serialize_unit_struct(self, name: &'static str) -> Result<()> {
-        if self.struct_names {
-            self.output += name;
+        if self.struct_names() {
+            self.write_identifier(name)?;
 
             Ok(())
         } else {
@@ -414,8 +1343,13 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         }
     }
 
-    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<()> {
-        self.output += variant;
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.write_variant(name, variant)?;
 
         Ok(())
     }
@@ -424,19 +1358,44 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        if self.struct_names {
-            self.output += name;
+        self.map_key_start = None;
+
+        if name == SET_NAME {
+            self.set_start = Some(self.output.len());
+
+            return value.serialize(&mut *self);
         }
 
-        self.output += "(";
-        value.serialize(&mut *self)?;
-        self.output += ")";
+        if name == DATETIME_NAME {
+            #[cfg(feature = "datetime")]
+            {
+                self.datetime_start = Some(self.output.len());
+            }
+
+            return value.serialize(&mut *self);
+        }
+
+        self.push_type_rule(name);
+
+        if self.extensions().contains(Extensions::UNWRAP_NEWTYPES) {
+            value.serialize(&mut *self)?;
+        } else {
+            if self.struct_names() {
+                self.write_identifier(name)?;
+            }
+
+            self.formatter.begin_tuple(&mut self.output);
+            value.serialize(&mut *self)?;
+            self.formatter.end_tuple(&mut self.output);
+        }
+
+        self.pop_layout_rule();
         Ok(())
     }
 
     fn serialize_newtype_variant<T>(
         self,
-        _: &'static str,
+        name: &'static str,
         _: u32,
         variant: &'static str,
         value: &T,
@@ -444,39 +1403,49 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.output += variant;
-        self.output += "(";
-
-        value.serialize(&mut *self)?;
+        self.push_type_rule(name);
 
-        self.output += ")";
-        Ok(())
-    }
+        self.write_variant(name, variant)?;
 
-    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.output += "[";
+        if !self
+            .extensions()
+            .contains(Extensions::UNWRAP_VARIANT_NEWTYPES)
+        {
+            self.formatter.begin_tuple(&mut self.output);
+            value.serialize(&mut *self)?;
+            self.formatter.end_tuple(&mut self.output);
 
-        if let Some(len) = len {
-            self.is_empty = Some(len == 0);
+            self.pop_layout_rule();
+            return Ok(());
         }
 
-        self.start_indent();
+        // A struct or tuple value takes the place of the parentheses,
+        // any other value gets them inserted afterwards
+        let start = self.output.len();
+        let outer = self.newtype_variant_start.replace(start);
 
-        if let Some((_, ref mut pretty)) = self.pretty {
-            pretty.sequence_index.push(0);
+        value.serialize(&mut *self)?;
+
+        if self.newtype_variant_start == Some(start) {
+            let mut begin = String::new();
+            self.formatter.begin_tuple(&mut begin);
+            self.output.insert_str(start, &begin);
+            self.formatter.end_tuple(&mut self.output);
         }
+        self.newtype_variant_start = outer;
 
-        Ok(self)
+        self.pop_layout_rule();
+        Ok(())
     }
 
-    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
-        self.output += "(";
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.start_seq(len);
 
-        if self.separate_tuple_members() {
-            self.is_empty = Some(len == 0);
+        Ok(self)
+    }
 
-            self.start_indent();
-        }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.start_tuple(None, Some(len))?;
 
         Ok(self)
     }
@@ -486,68 +1455,65 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        if self.struct_names {
-            self.output += name;
-        }
+        self.push_type_rule(name);
+
+        let name = if self.struct_names() {
+            Some(name)
+        } else {
+            None
+        };
+        self.start_tuple(name, Some(len))?;
 
-        self.serialize_tuple(len)
+        Ok(self)
     }
 
     fn serialize_tuple_variant(
         self,
-        _: &'static str,
+        name: &'static str,
         _: u32,
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.output += variant;
-        self.output += "(";
-
-        if self.separate_tuple_members() {
-            self.is_empty = Some(len == 0);
+        self.push_type_rule(name);
 
-            self.start_indent();
-        }
+        // The variant name is written first, so that it is kept within
+        // the parentheses of an unwrapped newtype variant
+        self.write_variant(name, variant)?;
+        self.start_tuple(None, Some(len))?;
 
         Ok(self)
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
-        self.output += "{";
-
-        if let Some(len) = len {
-            self.is_empty = Some(len == 0);
-        }
-
-        self.start_indent();
+        self.start_map(len);
 
         Ok(self)
     }
 
     fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
-        if self.struct_names {
-            self.output += name;
-        }
-        self.output += "(";
+        self.push_type_rule(name);
 
-        self.is_empty = Some(len == 0);
-        self.start_indent();
+        let name = if self.struct_names() {
+            Some(name)
+        } else {
+            None
+        };
+        self.start_struct(name, Some(len))?;
 
         Ok(self)
     }
 
     fn serialize_struct_variant(
         self,
-        _: &'static str,
+        name: &'static str,
         _: u32,
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.output += variant;
-        self.output += "(";
+        self.push_type_rule(name);
 
-        self.is_empty = Some(len == 0);
-        self.start_indent();
+        self.write_variant(name, variant)?;
+        self.start_struct(None, Some(len))?;
 
         Ok(self)
     }
@@ -561,36 +1527,28 @@ impl<'a> ser::SerializeSeq for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.indent();
+        let has_comments = self.has_comments();
+        if has_comments {
+            let own_line = self.is_line_start();
+            self.push_path(self.element_index().to_string(), own_line)?;
+        }
 
-        value.serialize(&mut **self)?;
-        self.output += ",";
+        self.start_seq_element();
 
-        if let Some((ref config, ref mut pretty)) = self.pretty {
-            if pretty.indent < config.depth_limit {
-                if config.enumerate_arrays {
-                    assert!(config.new_line.contains('\n'));
-                    let index = pretty.sequence_index.last_mut().unwrap();
-                    //TODO: when /**/ comments are supported, prepend the index
-                    // to an element instead of appending it.
-                    write!(self.output, "// [{}]", index).unwrap();
-                    *index += 1;
-                }
-                self.output += &config.new_line;
-            }
+        self.serialize_within_width(value, 1)?;
+
+        self.end_seq_element();
+
+        if has_comments {
+            self.pop_path();
         }
 
         Ok(())
     }
 
     fn end(self) -> Result<()> {
-        self.end_indent();
-
-        if let Some((_, ref mut pretty)) = self.pretty {
-            pretty.sequence_index.pop();
-        }
+        self.end_seq();
 
-        self.output += "]";
         Ok(())
     }
 }
@@ -603,35 +1561,27 @@ impl<'a> ser::SerializeTuple for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        if self.separate_tuple_members() {
-            self.indent();
+        let has_comments = self.has_comments();
+        if has_comments {
+            let own_line = self.separate_tuple_members() && self.is_line_start();
+            self.push_path(self.element_index().to_string(), own_line)?;
         }
 
-        value.serialize(&mut **self)?;
-        self.output += ",";
+        self.start_tuple_element();
 
-        if let Some((ref config, ref pretty)) = self.pretty {
-            if pretty.indent < config.depth_limit {
-                self.output += if self.separate_tuple_members() {
-                    &config.new_line
-                } else {
-                    " "
-                };
-            }
+        self.serialize_within_width(value, 1)?;
+
+        self.end_tuple_element();
+
+        if has_comments {
+            self.pop_path();
         }
 
         Ok(())
     }
 
     fn end(self) -> Result<()> {
-        if self.separate_tuple_members() {
-            self.end_indent();
-        } else if self.is_pretty() {
-            self.output.pop();
-            self.output.pop();
-        }
-
-        self.output += ")";
+        self.end_tuple();
 
         Ok(())
     }
@@ -650,7 +1600,11 @@ impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
     }
 
     fn end(self) -> Result<()> {
-        ser::SerializeTuple::end(self)
+        ser::SerializeTuple::end(&mut *self)?;
+
+        self.pop_layout_rule();
+
+        Ok(())
     }
 }
 
@@ -666,7 +1620,11 @@ impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
     }
 
     fn end(self) -> Result<()> {
-        ser::SerializeTuple::end(self)
+        ser::SerializeTuple::end(&mut *self)?;
+
+        self.pop_layout_rule();
+
+        Ok(())
     }
 }
 
@@ -678,7 +1636,13 @@ impl<'a> ser::SerializeMap for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.indent();
+        if self.has_comments() {
+            let mut key_ser = Serializer::new(None, self.struct_names());
+            key.serialize(&mut key_ser)?;
+            self.push_path(key_ser.output, true)?;
+        }
+
+        self.start_map_key();
 
         key.serialize(&mut **self)
     }
@@ -687,28 +1651,22 @@ impl<'a> ser::SerializeMap for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.output += ":";
+        self.start_map_value();
 
-        if self.is_pretty() {
-            self.output += " ";
-        }
+        self.serialize_within_width(value, 1)?;
 
-        value.serialize(&mut **self)?;
-        self.output += ",";
+        self.end_map_value();
 
-        if let Some((ref config, ref pretty)) = self.pretty {
-            if pretty.indent < config.depth_limit {
-                self.output += &config.new_line;
-            }
+        if self.has_comments() {
+            self.pop_path();
         }
 
         Ok(())
     }
 
     fn end(self) -> Result<()> {
-        self.end_indent();
+        self.end_map();
 
-        self.output += "}";
         Ok(())
     }
 }
@@ -721,31 +1679,31 @@ impl<'a> ser::SerializeStruct for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.indent();
+        let has_comments = self.has_comments();
+        if has_comments {
+            self.push_path(key.to_owned(), true)?;
+        }
 
-        self.output += key;
-        self.output += ":";
+        self.start_struct_field(key)?;
 
-        if self.is_pretty() {
-            self.output += " ";
-        }
+        self.push_field_rule(key);
+        self.serialize_within_width(value, 1)?;
+        self.pop_layout_rule();
 
-        value.serialize(&mut **self)?;
-        self.output += ",";
+        self.end_struct_field();
 
-        if let Some((ref config, ref pretty)) = self.pretty {
-            if pretty.indent < config.depth_limit {
-                self.output += &config.new_line;
-            }
+        if has_comments {
+            self.pop_path();
         }
 
         Ok(())
     }
 
     fn end(self) -> Result<()> {
-        self.end_indent();
+        self.end_struct();
+
+        self.pop_layout_rule();
 
-        self.output += ")";
         Ok(())
     }
 }
@@ -800,7 +1758,7 @@ mod tests {
     fn test_struct() {
         let my_struct = MyStruct { x: 4.0, y: 7.0 };
 
-        assert_eq!(to_string(&my_struct).unwrap(), "(x:4,y:7,)");
+        assert_eq!(to_string(&my_struct).unwrap(), "(x:4.0,y:7.0,)");
 
         #[derive(Serialize)]
         struct NewType(i32);
@@ -810,7 +1768,7 @@ mod tests {
         #[derive(Serialize)]
         struct TupleStruct(f32, f32);
 
-        assert_eq!(to_string(&TupleStruct(2.0, 5.0)).unwrap(), "(2,5,)");
+        assert_eq!(to_string(&TupleStruct(2.0, 5.0)).unwrap(), "(2.0,5.0,)");
     }
 
     #[test]
@@ -868,6 +1826,213 @@ mod tests {
         assert_eq!(to_string(&r#""Quoted""#).unwrap(), r#""\"Quoted\"""#);
     }
 
+    #[test]
+    fn test_formatter() {
+        /// Aligns the colons of fields and writes floats with two decimals.
+        struct Aligned {
+            field_len: usize,
+        }
+
+        impl Formatter for Aligned {
+            fn write_field_name(&mut self, out: &mut String, name: &str) {
+                self.field_len = name.len();
+                out.push_str(name);
+            }
+
+            fn write_field_separator(&mut self, out: &mut String, _: bool) {
+                out.push_str(&" ".repeat(4 - self.field_len));
+                out.push_str(": ");
+            }
+
+            fn write_f32(&mut self, out: &mut String, v: f32, mut format: FloatFormat) {
+                format.precision = Some(2);
+                format.write_f32(out, v);
+            }
+        }
+
+        #[derive(Serialize)]
+        struct Aabb {
+            x: f32,
+            size: (f32, f32),
+        }
+
+        let mut s = Serializer::new(
+            Some(PrettyConfig::new().with_new_line("\n".to_owned())),
+            false,
+        )
+        .with_formatter(Aligned { field_len: 0 });
+        Aabb {
+            x: 1.0,
+            size: (0.5, 2.125),
+        }
+        .serialize(&mut s)
+        .unwrap();
+
+        assert_eq!(
+            s.into_output_string(),
+            "(
+    x   : 1.00,
+    size: (0.50, 2.12),
+)"
+        );
+    }
+
+    #[test]
+    fn test_floats() {
+        assert_eq!(to_string(&4.0f64).unwrap(), "4.0");
+        assert_eq!(to_string(&-0.0f64).unwrap(), "-0.0");
+        assert_eq!(to_string(&0.1f32).unwrap(), "0.1");
+        assert_eq!(to_string(&1e-7f64).unwrap(), "0.0000001");
+        assert_eq!(to_string(&1e20f64).unwrap(), "100000000000000000000.0");
+        assert_eq!(to_string(&f64::NAN).unwrap(), "NaN");
+        assert_eq!(to_string(&f32::NEG_INFINITY).unwrap(), "-inf");
+
+        let values = (1.0 / 3.0, 1234.5f32, -1.5e-7, 2e10);
+        let pretty = |config: PrettyConfig| to_string_pretty(&values, config).unwrap();
+
+        assert_eq!(
+            pretty(PrettyConfig::new().with_float_precision(3)),
+            "(0.333, 1234.500, -0.000, 20000000000.000)"
+        );
+        assert_eq!(
+            pretty(PrettyConfig::new().with_float_precision(0)),
+            "(0., 1234., -0., 20000000000.)"
+        );
+        assert_eq!(
+            pretty(PrettyConfig::new().with_float_significant_digits(2)),
+            "(0.33, 1200.0, -0.00000015, 20000000000.0)"
+        );
+        assert_eq!(
+            pretty(PrettyConfig::new().with_float_scientific_threshold(6)),
+            "(0.3333333333333333, 1234.5, -1.5e-7, 2.0e10)"
+        );
+    }
+
+    #[test]
+    fn test_max_width() {
+        #[derive(Serialize)]
+        struct Layout {
+            small: Vec<u8>,
+            large: Vec<&'static str>,
+            pair: (f32, f32),
+            nested: Vec<MyStruct>,
+        }
+
+        let layout = Layout {
+            small: vec![1, 2, 3],
+            large: vec!["a rather long string", "which does not fit"],
+            pair: (0.5, 1.5),
+            nested: vec![MyStruct { x: 1.0, y: 2.0 }],
+        };
+        let config = PrettyConfig::new()
+            .with_new_line("\n".to_owned())
+            .with_max_width(36);
+
+        assert_eq!(
+            to_string_pretty(&layout, config).unwrap(),
+            "(
+    small: [1, 2, 3],
+    large: [
+        \"a rather long string\",
+        \"which does not fit\",
+    ],
+    pair: (0.5, 1.5),
+    nested: [(x: 1.0, y: 2.0)],
+)"
+        );
+
+        let config = PrettyConfig::new()
+            .with_new_line("\n".to_owned())
+            .with_max_width(12);
+
+        assert_eq!(
+            to_string_pretty(&(1000, 2000, 3000), config).unwrap(),
+            "(
+    1000,
+    2000,
+    3000,
+)"
+        );
+    }
+
+    #[test]
+    fn test_layout_rules() {
+        #[derive(Serialize)]
+        struct Mesh {
+            name: &'static str,
+            origin: MyStruct,
+            data: Vec<u8>,
+            pairs: Vec<(u8, u8)>,
+        }
+
+        let mesh = Mesh {
+            name: "cube",
+            origin: MyStruct { x: 1.0, y: 2.0 },
+            data: vec![1, 2, 3, 4, 5],
+            pairs: vec![(1, 2)],
+        };
+        let config = PrettyConfig::new()
+            .with_new_line("\n".to_owned())
+            .with_max_width(80)
+            .with_type_rule(
+                "Mesh".to_owned(),
+                LayoutRule::new().with_inline(false).with_struct_names(true),
+            )
+            .with_type_rule(
+                "MyStruct".to_owned(),
+                LayoutRule::new().with_inline(true).with_struct_names(false),
+            )
+            .with_field_rule("data".to_owned(), LayoutRule::new().with_items_per_line(2));
+
+        assert_eq!(
+            to_string_pretty(&mesh, config).unwrap(),
+            "Mesh(
+    name: \"cube\",
+    origin: (x: 1.0, y: 2.0),
+    data: [
+        1, 2,
+        3, 4,
+        5,
+    ],
+    pairs: [
+        (
+            1,
+            2,
+        ),
+    ],
+)"
+        );
+    }
+
+    #[test]
+    fn test_variant_layout_rules() {
+        #[derive(Serialize)]
+        enum Shape {
+            Point(Vec<u8>),
+            Line(u8, u8),
+            Rect { w: u8, h: u8 },
+        }
+
+        let shapes = vec![
+            Shape::Point(vec![1, 2]),
+            Shape::Line(3, 4),
+            Shape::Rect { w: 5, h: 6 },
+        ];
+        let config = PrettyConfig::new()
+            .with_new_line("\n".to_owned())
+            .with_separate_tuple_members(true)
+            .with_type_rule("Shape".to_owned(), LayoutRule::new().with_inline(true));
+
+        assert_eq!(
+            to_string_pretty(&shapes, config).unwrap(),
+            "[
+    Point([1, 2]),
+    Line(3, 4),
+    Rect(w: 5, h: 6),
+]"
+        );
+    }
+
     #[test]
     fn test_byte_stream() {
         use serde_bytes;
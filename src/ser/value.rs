@@ -1,6 +1,6 @@
 use serde::ser::{Serialize, Serializer};
 
-use crate::value::Value;
+use crate::value::{Value, DATETIME_NAME, SET_NAME};
 
 impl Serialize for Value {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -16,7 +16,9 @@ impl Serialize for Value {
             Value::Option(None) => serializer.serialize_none(),
             Value::String(ref s) => serializer.serialize_str(s),
             Value::Seq(ref s) => Serialize::serialize(s, serializer),
+            Value::Set(ref s) => serializer.serialize_newtype_struct(SET_NAME, s),
             Value::Unit => serializer.serialize_unit(),
+            Value::DateTime(ref s) => serializer.serialize_newtype_struct(DATETIME_NAME, s),
         }
     }
 }
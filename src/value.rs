@@ -200,6 +200,30 @@ impl Ord for Number {
     }
 }
 
+/// Name of the newtype struct `Value::Set` serializes as, which tells the
+/// serializer to write its sequence as a set literal.
+pub(crate) const SET_NAME: &str = "$__ron_private_Set";
+
+/// Key of the single-entry map a set literal is visited as when
+/// deserializing a `Value`, which tells `Value` to keep it apart from a
+/// list.
+pub(crate) const SET_KEY: &str = "$__ron_private_set";
+
+/// Name of the newtype struct `Value::DateTime` serializes as, which tells
+/// the serializer to write its string as a date/time literal.
+pub(crate) const DATETIME_NAME: &str = "$__ron_private_DateTime";
+
+/// Key of the single-entry map a date/time literal is visited as when
+/// deserializing a `Value`, which tells `Value` to keep it apart from a
+/// string.
+pub(crate) const DATETIME_KEY: &str = "$__ron_private_datetime";
+
+/// Name of the newtype struct `Value` deserializes as, which tells the
+/// deserializer that a set or date/time literal may be visited as a map
+/// with `SET_KEY` or `DATETIME_KEY`. Any other visitor sees a set literal
+/// as a sequence and a date/time literal as a string.
+pub(crate) const VALUE_NAME: &str = "$__ron_private_Value";
+
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Value {
     Bool(bool),
@@ -209,7 +233,11 @@ pub enum Value {
     Option(Option<Box<Value>>),
     String(String),
     Seq(Vec<Value>),
+    /// The elements of a `#{..}` set literal, in the order they are written.
+    Set(Vec<Value>),
     Unit,
+    /// An RFC 3339 date, time or date-time literal, kept as written.
+    DateTime(String),
 }
 
 impl Value {
@@ -248,11 +276,12 @@ impl<'de> Deserializer<'de> for Value {
             Value::Option(Some(o)) => visitor.visit_some(*o),
             Value::Option(None) => visitor.visit_none(),
             Value::String(s) => visitor.visit_string(s),
-            Value::Seq(mut seq) => {
+            Value::Seq(mut seq) | Value::Set(mut seq) => {
                 seq.reverse();
                 visitor.visit_seq(Seq { seq })
             }
             Value::Unit => visitor.visit_unit(),
+            Value::DateTime(s) => visitor.visit_string(s),
         }
     }
 
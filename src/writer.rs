@@ -0,0 +1,419 @@
+//! Low-level RON writer, for emitting RON without going through serde.
+
+use serde::ser::Serializer as _;
+
+use crate::error::{Error, ErrorCode, Position, Result};
+use crate::ser::{PrettyConfig, Serializer};
+
+/// A compound value which has been started but not yet ended.
+enum Frame {
+    Some { has_value: bool },
+    Struct { has_field: bool },
+    Tuple,
+    Seq,
+    Map { has_key: bool },
+}
+
+/// Writes RON token by token.
+///
+/// The writer uses the same `PrettyConfig` and layout as `Serializer`,
+/// but is driven by explicit calls instead of a `Serialize` implementation.
+/// Calls which would produce malformed RON, like a struct value without a
+/// field name or an unbalanced `end`, return an error.
+///
+/// # Examples
+///
+/// ```
+/// use ron::writer::Writer;
+///
+/// let mut writer = Writer::new(None);
+///
+/// writer.begin_struct(Some("Point")).unwrap();
+/// writer.field("x").unwrap();
+/// writer.value_i64(1).unwrap();
+/// writer.field("y").unwrap();
+/// writer.value_i64(-2).unwrap();
+/// writer.end().unwrap();
+///
+/// assert_eq!(writer.into_output_string().unwrap(), "Point(x:1,y:-2,)");
+/// ```
+pub struct Writer {
+    ser: Serializer,
+    stack: Vec<Frame>,
+    has_root: bool,
+}
+
+impl Writer {
+    /// Creates a new `Writer`, pretty-printing if a `PrettyConfig` is given.
+    pub fn new(config: Option<PrettyConfig>) -> Self {
+        Writer {
+            ser: Serializer::new(config, false),
+            stack: Vec::new(),
+            has_root: false,
+        }
+    }
+
+    /// Consumes `self` and returns the built `String`.
+    ///
+    /// Fails if no value has been written or a compound value is still open.
+    pub fn into_output_string(self) -> Result<String> {
+        if !self.stack.is_empty() {
+            return err("unclosed compound value");
+        }
+        if !self.has_root {
+            return err("no value written");
+        }
+
+        Ok(self.ser.into_output_string())
+    }
+
+    /// Writes a comment before the next field, element, map entry or root value.
+    ///
+    /// In pretty multi-line output, the comment is written as `//` lines;
+    /// otherwise it becomes a `/* */` block comment.
+    pub fn comment(&mut self, text: &str) -> Result<()> {
+        let own_line = match self.stack.last() {
+            None if self.has_root => return err("comment after the root value"),
+            None => true,
+            Some(Frame::Some { .. }) => return err("comment inside `Some`"),
+            Some(Frame::Struct { has_field: true }) | Some(Frame::Map { has_key: true }) => {
+                return err("comment between a key and its value")
+            }
+            Some(Frame::Tuple) => self.ser.separate_tuple_members() && self.ser.is_line_start(),
+            Some(Frame::Seq) => self.ser.is_line_start(),
+            Some(_) => true,
+        };
+
+        self.ser.write_comment(text, own_line)
+    }
+
+    /// Writes the name of the next struct field.
+    pub fn field(&mut self, name: &str) -> Result<()> {
+        match self.stack.last_mut() {
+            Some(Frame::Struct { has_field }) if !*has_field => {
+                *has_field = true;
+                self.ser.start_struct_field(name)
+            }
+            Some(Frame::Struct { .. }) => err("expected a value for the previous field"),
+            _ => err("field outside of a struct"),
+        }
+    }
+
+    /// Begins a struct, optionally preceded by its name.
+    pub fn begin_struct(&mut self, name: Option<&str>) -> Result<()> {
+        self.begin_value()?;
+        self.ser.start_struct(name, None)?;
+        self.stack.push(Frame::Struct { has_field: false });
+
+        Ok(())
+    }
+
+    /// Begins a tuple or tuple struct, optionally preceded by its name.
+    pub fn begin_tuple(&mut self, name: Option<&str>) -> Result<()> {
+        self.begin_value()?;
+        self.ser.start_tuple(name, None)?;
+        self.stack.push(Frame::Tuple);
+
+        Ok(())
+    }
+
+    /// Begins a list.
+    pub fn begin_seq(&mut self) -> Result<()> {
+        self.begin_value()?;
+        self.ser.start_seq(None);
+        self.stack.push(Frame::Seq);
+
+        Ok(())
+    }
+
+    /// Begins a map. Keys and values are written alternately.
+    pub fn begin_map(&mut self) -> Result<()> {
+        self.begin_value()?;
+        self.ser.start_map(None);
+        self.stack.push(Frame::Map { has_key: false });
+
+        Ok(())
+    }
+
+    /// Begins an optional value which must contain exactly one value.
+    pub fn begin_some(&mut self) -> Result<()> {
+        self.begin_value()?;
+        self.ser.start_some();
+        self.stack.push(Frame::Some { has_value: false });
+
+        Ok(())
+    }
+
+    /// Ends the innermost compound value.
+    pub fn end(&mut self) -> Result<()> {
+        match self.stack.last() {
+            None => return err("end without a compound value"),
+            Some(Frame::Some { has_value: false }) => return err("expected a value in `Some`"),
+            Some(Frame::Struct { has_field: true }) => {
+                return err("expected a value for the previous field")
+            }
+            Some(Frame::Map { has_key: true }) => return err("expected a value for the map key"),
+            Some(_) => {}
+        }
+
+        match self.stack.pop().unwrap() {
+            Frame::Some { .. } => self.ser.end_some(),
+            Frame::Struct { .. } => self.ser.end_struct(),
+            Frame::Tuple => self.ser.end_tuple(),
+            Frame::Seq => self.ser.end_seq(),
+            Frame::Map { .. } => self.ser.end_map(),
+        }
+
+        self.end_value();
+
+        Ok(())
+    }
+
+    /// Writes a boolean.
+    pub fn value_bool(&mut self, v: bool) -> Result<()> {
+        self.begin_value()?;
+        self.ser.serialize_bool(v)?;
+        self.end_value();
+
+        Ok(())
+    }
+
+    /// Writes a signed integer.
+    pub fn value_i64(&mut self, v: i64) -> Result<()> {
+        self.begin_value()?;
+        self.ser.serialize_i64(v)?;
+        self.end_value();
+
+        Ok(())
+    }
+
+    /// Writes an unsigned integer.
+    pub fn value_u64(&mut self, v: u64) -> Result<()> {
+        self.begin_value()?;
+        self.ser.serialize_u64(v)?;
+        self.end_value();
+
+        Ok(())
+    }
+
+    /// Writes a floating point number.
+    pub fn value_f64(&mut self, v: f64) -> Result<()> {
+        self.begin_value()?;
+        self.ser.serialize_f64(v)?;
+        self.end_value();
+
+        Ok(())
+    }
+
+    /// Writes a char.
+    pub fn value_char(&mut self, v: char) -> Result<()> {
+        self.begin_value()?;
+        self.ser.serialize_char(v)?;
+        self.end_value();
+
+        Ok(())
+    }
+
+    /// Writes an escaped string.
+    pub fn value_str(&mut self, v: &str) -> Result<()> {
+        self.begin_value()?;
+        self.ser.serialize_str(v)?;
+        self.end_value();
+
+        Ok(())
+    }
+
+    /// Writes a byte buffer.
+    pub fn value_bytes(&mut self, v: &[u8]) -> Result<()> {
+        self.begin_value()?;
+        self.ser.serialize_bytes(v)?;
+        self.end_value();
+
+        Ok(())
+    }
+
+    /// Writes the unit value `()`.
+    pub fn value_unit(&mut self) -> Result<()> {
+        self.begin_value()?;
+        self.ser.serialize_unit()?;
+        self.end_value();
+
+        Ok(())
+    }
+
+    /// Writes `None`.
+    pub fn value_none(&mut self) -> Result<()> {
+        self.begin_value()?;
+        self.ser.serialize_none()?;
+        self.end_value();
+
+        Ok(())
+    }
+
+    /// Writes a bare identifier, i.e. a unit struct or unit enum variant.
+    pub fn value_identifier(&mut self, name: &str) -> Result<()> {
+        self.begin_value()?;
+        self.ser.write_identifier(name)?;
+        self.end_value();
+
+        Ok(())
+    }
+
+    fn begin_value(&mut self) -> Result<()> {
+        match self.stack.last() {
+            None if self.has_root => err("root value already written"),
+            Some(Frame::Some { has_value: true }) => err("`Some` already has a value"),
+            Some(Frame::Struct { has_field: false }) => err("expected a field name"),
+            Some(Frame::Tuple) => {
+                self.ser.start_tuple_element();
+
+                Ok(())
+            }
+            Some(Frame::Seq) => {
+                self.ser.start_seq_element();
+
+                Ok(())
+            }
+            Some(Frame::Map { has_key: false }) => {
+                self.ser.start_map_key();
+
+                Ok(())
+            }
+            Some(Frame::Map { has_key: true }) => {
+                self.ser.start_map_value();
+
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn end_value(&mut self) {
+        match self.stack.last_mut() {
+            None => self.has_root = true,
+            Some(Frame::Some { has_value }) => *has_value = true,
+            Some(Frame::Struct { has_field }) => {
+                *has_field = false;
+                self.ser.end_struct_field();
+            }
+            Some(Frame::Tuple) => self.ser.end_tuple_element(),
+            Some(Frame::Seq) => self.ser.end_seq_element(),
+            Some(Frame::Map { has_key }) => {
+                if *has_key {
+                    self.ser.end_map_value();
+                }
+                *has_key = !*has_key;
+            }
+        }
+    }
+}
+
+fn err<T>(reason: &'static str) -> Result<T> {
+    Err(Error {
+        code: ErrorCode::InvalidWriterState(reason),
+        position: Position {
+            line: 0,
+            col: 0,
+            file: None,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact() {
+        let mut writer = Writer::new(None);
+
+        writer.begin_seq().unwrap();
+        writer.begin_tuple(None).unwrap();
+        writer.value_bool(true).unwrap();
+        writer.value_str("a\"b").unwrap();
+        writer.end().unwrap();
+        writer.begin_map().unwrap();
+        writer.value_char('k').unwrap();
+        writer.begin_some().unwrap();
+        writer.value_u64(3).unwrap();
+        writer.end().unwrap();
+        writer.end().unwrap();
+        writer.value_identifier("Unit").unwrap();
+        writer.end().unwrap();
+
+        assert_eq!(
+            writer.into_output_string().unwrap(),
+            "[(true,\"a\\\"b\",),{'k':Some(3),},Unit,]"
+        );
+    }
+
+    #[test]
+    fn test_pretty_comments() {
+        let mut writer = Writer::new(Some(PrettyConfig::new().with_new_line("\n".to_owned())));
+
+        writer.comment("Generated").unwrap();
+        writer.begin_struct(Some("Config")).unwrap();
+        writer.comment("The name\nof the thing").unwrap();
+        writer.field("name").unwrap();
+        writer.value_str("ron").unwrap();
+        writer.field("sizes").unwrap();
+        writer.begin_tuple(None).unwrap();
+        writer.comment("width").unwrap();
+        writer.value_i64(1).unwrap();
+        writer.value_i64(2).unwrap();
+        writer.end().unwrap();
+        writer.end().unwrap();
+
+        assert_eq!(
+            writer.into_output_string().unwrap(),
+            "// Generated
+Config(
+    // The name
+    // of the thing
+    name: \"ron\",
+    sizes: (/* width */ 1, 2),
+)"
+        );
+    }
+
+    #[test]
+    fn test_well_formedness() {
+        let mut writer = Writer::new(None);
+
+        assert_eq!(writer.end(), err("end without a compound value"));
+        writer.begin_struct(None).unwrap();
+        assert_eq!(writer.value_i64(1), err("expected a field name"));
+        writer.field("a").unwrap();
+        assert_eq!(
+            writer.field("b"),
+            err("expected a value for the previous field")
+        );
+        assert_eq!(
+            writer.comment("c"),
+            err("comment between a key and its value")
+        );
+        assert_eq!(writer.end(), err("expected a value for the previous field"));
+        writer.value_unit().unwrap();
+        writer.end().unwrap();
+        assert_eq!(writer.value_none(), err("root value already written"));
+
+        let mut writer = Writer::new(None);
+        writer.begin_seq().unwrap();
+        assert_eq!(
+            writer.comment("*/"),
+            err_code(ErrorCode::UnrepresentableComment)
+        );
+        assert!(writer.into_output_string().is_err());
+    }
+
+    fn err_code(code: ErrorCode) -> Result<()> {
+        Err(Error {
+            code,
+            position: Position {
+                line: 0,
+                col: 0,
+                file: None,
+            },
+        })
+    }
+}
@@ -0,0 +1,196 @@
+use ron::{
+    de::{from_str, from_str_with_comments},
+    error::{Error, ErrorCode, Position},
+    value::{Number, Value},
+};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct Retry {
+    timeout: u32,
+    retries: u8,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Mode {
+    Fast,
+    Tuned(u8),
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Config {
+    default: Retry,
+    primary: Retry,
+    fallback: Retry,
+    modes: Vec<Mode>,
+    limit: u64,
+}
+
+#[test]
+fn test_reuse() {
+    let retry = Retry {
+        timeout: 30,
+        retries: 3,
+    };
+
+    assert_eq!(
+        from_str(
+            "#![enable(anchors)]
+            (
+                default: &defaults (timeout: 30, retries: 3),
+                primary: *defaults,
+                fallback: *defaults,
+                modes: [&tuned Tuned(&n 7), *tuned, Fast, Tuned(*n)],
+                limit: *n,
+            )"
+        ),
+        Ok(Config {
+            default: retry.clone(),
+            primary: retry.clone(),
+            fallback: retry,
+            modes: vec![Mode::Tuned(7), Mode::Tuned(7), Mode::Fast, Mode::Tuned(7)],
+            limit: 7,
+        })
+    );
+
+    // anchors in a replayed value are the same anchors again
+    assert_eq!(
+        from_str::<Vec<Vec<u8>>>("#![enable(anchors)] [&a [&b 1, *b], *a, [*b]]"),
+        Ok(vec![vec![1, 1], vec![1, 1], vec![1]])
+    );
+
+    // anchors are kept across the elements of a repetition
+    assert_eq!(
+        from_str::<(Vec<u8>, u8)>("#![enable(anchors, array_repetition)] ([&a 5; 2], *a)"),
+        Ok((vec![5, 5], 5))
+    );
+
+    assert_eq!(
+        "#![enable(anchors)] [&x (1, 2), *x]".parse(),
+        Ok(Value::Seq(vec![
+            Value::Seq(vec![
+                Value::Number(Number::new(1.0)),
+                Value::Number(Number::new(2.0)),
+            ]),
+            Value::Seq(vec![
+                Value::Number(Number::new(1.0)),
+                Value::Number(Number::new(2.0)),
+            ]),
+        ]))
+    );
+}
+
+#[test]
+fn test_unknown_alias() {
+    assert_eq!(
+        from_str::<Vec<u8>>("#![enable(anchors)] [1, *one]"),
+        Err(Error {
+            code: ErrorCode::UnknownAlias("one".to_owned()),
+            position: Position {
+                line: 1,
+                col: 25,
+                file: None
+            },
+        })
+    );
+
+    // a value cannot reference its own anchor
+    assert_eq!(
+        from_str::<Value>("#![enable(anchors)] &a [1, *a]").map_err(|e| e.code),
+        Err(ErrorCode::UnknownAlias("a".to_owned()))
+    );
+
+    // a name cannot be used for another anchor
+    assert_eq!(
+        from_str::<Vec<u8>>("#![enable(anchors)] [&a 1, *a, &a 2, *a]"),
+        Err(Error {
+            code: ErrorCode::DuplicateAnchor("a".to_owned()),
+            position: Position {
+                line: 1,
+                col: 32,
+                file: None
+            },
+        })
+    );
+    assert_eq!(
+        from_str::<Value>("#![enable(anchors)] (a: &x 1, b: &y [*x], c: &x 2, d: *y)")
+            .map_err(|e| e.code),
+        Err(ErrorCode::DuplicateAnchor("x".to_owned()))
+    );
+    assert_eq!(
+        from_str::<Value>("#![enable(anchors)] [&x 1, &x [*x], *x]").map_err(|e| e.code),
+        Err(ErrorCode::DuplicateAnchor("x".to_owned()))
+    );
+
+    // without the extension, anchors and aliases are not valid
+    assert!(from_str::<Vec<u8>>("[&a 1, *a]").is_err());
+}
+
+#[test]
+fn test_limits() {
+    let ten = |name: &str| vec![format!("*{}", name); 10].join(", ");
+    let bomb = format!(
+        "#![enable(anchors)] (&a [{}], &b [{}], &c [{}], &d [{}], &e [{}])",
+        ["1"; 10].join(", "),
+        ten("a"),
+        ten("b"),
+        ten("c"),
+        ten("d"),
+    );
+
+    assert_eq!(
+        from_str::<Value>(&bomb).map_err(|e| e.code),
+        Err(ErrorCode::AliasLimitExceeded("too many aliases"))
+    );
+
+    let mut chain = String::from("#![enable(anchors)] [&a0 1");
+    for i in 1..=64 {
+        chain.push_str(&format!(", &a{} [*a{}]", i, i - 1));
+    }
+    assert!(from_str::<Value>(&format!("{}]", chain)).is_ok());
+
+    chain.push_str(", [*a64]]");
+    assert_eq!(
+        from_str::<Value>(&chain).map_err(|e| e.code),
+        Err(ErrorCode::AliasLimitExceeded("aliases nested too deeply"))
+    );
+
+    // looking at map keys for their comments replays aliases as well
+    let keys = format!(
+        "#![enable(anchors)] (&a [{}], &b [{}], &c [{}], &d [{}], {{ *d: 0, *d: 1, *d: 2, *d: 3 }})",
+        ["1"; 10].join(", "),
+        ten("a"),
+        ten("b"),
+        ten("c"),
+    );
+    assert!(from_str::<Value>(&keys).is_ok());
+    assert_eq!(
+        from_str_with_comments::<Value>(&keys).map_err(|e| e.code),
+        Err(ErrorCode::AliasLimitExceeded("too many aliases"))
+    );
+}
+
+#[test]
+fn test_newtype_variants() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Shape {
+        W(Point),
+    }
+
+    assert_eq!(
+        from_str(
+            "#![enable(anchors, unwrap_variant_newtypes)] [W(&p x: 1, y: 2), W(*p), W(x: 3, y: 4)]"
+        ),
+        Ok(vec![
+            Shape::W(Point { x: 1, y: 2 }),
+            Shape::W(Point { x: 1, y: 2 }),
+            Shape::W(Point { x: 3, y: 4 }),
+        ])
+    );
+}
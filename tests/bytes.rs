@@ -0,0 +1,87 @@
+use ron::ser::{to_string_pretty, ByteEncoding, PrettyConfig};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Blob {
+    #[serde(with = "serde_bytes")]
+    data: Vec<u8>,
+}
+
+fn blob() -> Blob {
+    Blob {
+        data: vec![0, b'a', b'"', b'\\', b'\n', 0xff],
+    }
+}
+
+fn serialize(byte_encoding: ByteEncoding) -> String {
+    let config = PrettyConfig::new()
+        .with_new_line("\n".to_owned())
+        .with_byte_encoding(byte_encoding)
+        .with_max_width(80);
+
+    to_string_pretty(&blob(), config).unwrap()
+}
+
+#[test]
+fn test_byte_encodings() {
+    assert_eq!(serialize(ByteEncoding::Base64), "(data: \"AGEiXAr/\")");
+    assert_eq!(
+        serialize(ByteEncoding::ByteString),
+        "#![enable(byte_strings)]\n(data: b\"\\0a\\\"\\\\\\n\\xff\")"
+    );
+    assert_eq!(
+        serialize(ByteEncoding::Hex),
+        "#![enable(byte_strings)]\n(data: x\"0061225c0aff\")"
+    );
+    assert_eq!(
+        serialize(ByteEncoding::List),
+        "(data: [0, 97, 34, 92, 10, 255])"
+    );
+
+    for &byte_encoding in &[
+        ByteEncoding::Base64,
+        ByteEncoding::ByteString,
+        ByteEncoding::Hex,
+        ByteEncoding::List,
+    ] {
+        assert_eq!(ron::de::from_str(&serialize(byte_encoding)), Ok(blob()));
+    }
+}
+
+#[test]
+fn test_byte_literals() {
+    assert_eq!(
+        ron::de::from_str(
+            "#![enable(byte_strings)] (data: [0x00, b'a', b'\"', b'\\\\', b'\\n', 255,])"
+        ),
+        Ok(blob())
+    );
+    assert_eq!(
+        ron::de::from_str("#![enable(byte_strings)] b'\\x7f'"),
+        Ok(0x7fu8)
+    );
+    assert_eq!(
+        ron::de::from_str("[]"),
+        Ok(serde_bytes::ByteBuf::from(vec![]))
+    );
+    assert_eq!(
+        ron::de::from_str::<ron::Value>("#![enable(byte_strings)] b\"ron\""),
+        Ok(ron::Value::String("ron".to_owned()))
+    );
+    assert!(ron::de::from_str::<Blob>("#![enable(byte_strings)] (data: x\"abc\")").is_err());
+
+    // without the extension, these are not part of the syntax
+    assert!(ron::de::from_str::<u8>("b'a'").is_err());
+    assert!(ron::de::from_str::<Blob>("(data: b\"ron\")").is_err());
+    assert!(ron::de::from_str::<Blob>("(data: x\"00\")").is_err());
+}
+
+#[test]
+fn test_byte_string_line_continuation() {
+    assert_eq!(
+        ron::de::from_str("#![enable(byte_strings)] (data: b\"ab\\\n    cd\")"),
+        Ok(Blob {
+            data: b"abcd".to_vec()
+        })
+    );
+}
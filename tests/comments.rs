@@ -46,7 +46,110 @@ fn test_unclosed() {
         ),
         Err(RonErr {
             code: ErrorCode::UnclosedBlockComment,
-            position: Position { col: 1, line: 9 }
+            position: Position {
+                col: 1,
+                line: 9,
+                file: None
+            }
         })
     );
 }
+
+#[test]
+fn test_serialize_comments() {
+    use ron::ser::{to_string_pretty, PrettyConfig};
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Config {
+        name: String,
+        size: (u32, u32),
+        levels: Vec<u8>,
+        keys: BTreeMap<String, bool>,
+    }
+
+    let config = Config {
+        name: "ron".to_owned(),
+        size: (2, 3),
+        levels: vec![4, 5],
+        keys: vec![("up".to_owned(), true)].into_iter().collect(),
+    };
+    let pretty = PrettyConfig::new()
+        .with_new_line("\n".to_owned())
+        .with_comment("name".to_owned(), "The name\nof the config".to_owned())
+        .with_comment("size.1".to_owned(), "height".to_owned())
+        .with_comment("levels.1".to_owned(), "last level".to_owned())
+        .with_comment("keys.\"up\"".to_owned(), "moves up".to_owned());
+
+    let s = to_string_pretty(&config, pretty).unwrap();
+    assert_eq!(
+        s,
+        "(
+    // The name
+    // of the config
+    name: \"ron\",
+    size: (2, /* height */ 3),
+    levels: [
+        4,
+        // last level
+        5,
+    ],
+    keys: {
+        // moves up
+        \"up\": true,
+    },
+)"
+    );
+    assert_eq!(from_str::<Config>(&s), Ok(config));
+}
+
+#[test]
+fn test_comments_roundtrip() {
+    use ron::de::from_str_with_comments;
+    use ron::ser::{to_string_pretty, PrettyConfig};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Settings {
+        volume: u8,
+        names: Vec<String>,
+    }
+
+    let input = "(
+    // Master volume
+    volume: 7,
+    names: [
+        \"a\",
+        // The second name
+        \"b\",
+    ],
+)";
+
+    let (settings, comments): (Settings, _) = from_str_with_comments(input).unwrap();
+    let mut config = PrettyConfig::new().with_new_line("\n".to_owned());
+    config.comments = comments;
+
+    assert_eq!(to_string_pretty(&settings, config).unwrap(), input);
+}
+
+#[test]
+fn test_unrepresentable_inline_comment() {
+    use ron::ser::{to_string_pretty, PrettyConfig};
+
+    let pretty = PrettyConfig::new()
+        .with_new_line("\n".to_owned())
+        .with_max_width(80)
+        .with_comment("1".to_owned(), "not */ inline".to_owned());
+
+    // the list would fit on one line, but the comment cannot be written there
+    assert_eq!(
+        to_string_pretty(&vec![1, 2], pretty),
+        Ok("[
+    1,
+    // not */ inline
+    2,
+]"
+        .to_owned())
+    );
+}
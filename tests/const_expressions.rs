@@ -0,0 +1,188 @@
+use ron::{
+    de::from_str,
+    error::{Error, ErrorCode, Position},
+    value::{Number, Value},
+};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Limits {
+    timeout: u32,
+    buffer: u64,
+    mask: u8,
+    offset: i16,
+    ratio: f32,
+}
+
+fn eval<'a, T: Deserialize<'a>>(s: &'a str) -> Result<T, Error> {
+    from_str(s)
+}
+
+#[test]
+fn test_typed() {
+    assert_eq!(
+        from_str(
+            "#![enable(const_expressions)]
+            (
+                timeout: 30 * 60, // 30 minutes
+                buffer: 1 << 20,
+                mask: 0b1100 | 0b0011 & 0b0110,
+                offset: -8 * (1 + 1) - -1,
+                ratio: 1.0 / 4,
+            )"
+        ),
+        Ok(Limits {
+            timeout: 1800,
+            buffer: 1048576,
+            mask: 0b1110,
+            offset: -15,
+            ratio: 0.25,
+        })
+    );
+
+    assert_eq!(eval("#![enable(const_expressions)] 2+3*4"), Ok(14u8));
+    assert_eq!(eval("#![enable(const_expressions)] (2+3)*4"), Ok(20u8));
+    assert_eq!(eval("#![enable(const_expressions)] 100 - 10 - 1"), Ok(89u8));
+    assert_eq!(eval("#![enable(const_expressions)] 17 % 5 ^ 3"), Ok(1u8));
+    assert_eq!(eval("#![enable(const_expressions)] 256 >> 4"), Ok(16u16));
+    assert_eq!(
+        eval("#![enable(const_expressions)] 6 /* six */ / 4"),
+        Ok(1u8)
+    );
+    assert_eq!(eval("#![enable(const_expressions)] 1e2 - 1"), Ok(99.0f64));
+
+    // without the extension, operators are not part of a number
+    assert_eq!(
+        eval::<u8>("2 + 3").map_err(|e| e.code),
+        Err(ErrorCode::TrailingCharacters)
+    );
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        eval::<u8>("#![enable(const_expressions)] 16 * 16"),
+        Err(Error {
+            code: ErrorCode::IntegerOutOfBounds,
+            position: Position {
+                line: 1,
+                col: 34,
+                file: None
+            },
+        })
+    );
+    assert_eq!(
+        eval::<u32>("#![enable(const_expressions)] 5 - 6 + 2").map_err(|e| e.code),
+        Err(ErrorCode::IntegerOutOfBounds)
+    );
+    assert_eq!(
+        eval::<u8>("#![enable(const_expressions)] 3 << 7").map_err(|e| e.code),
+        Err(ErrorCode::IntegerOutOfBounds)
+    );
+    assert_eq!(
+        eval::<i32>("#![enable(const_expressions)] 1 / (2 - 2)"),
+        Err(Error {
+            code: ErrorCode::InvalidExpression("division by zero"),
+            position: Position {
+                line: 1,
+                col: 33,
+                file: None
+            },
+        })
+    );
+    assert_eq!(
+        eval::<f64>("#![enable(const_expressions)] 1.0 | 2.0").map_err(|e| e.code),
+        Err(ErrorCode::InvalidExpression("bit operators need integers"))
+    );
+    assert_eq!(
+        eval::<u8>("#![enable(const_expressions)] (1 + 2").map_err(|e| e.code),
+        Err(ErrorCode::InvalidExpression("expected `)`"))
+    );
+}
+
+#[test]
+fn test_any() {
+    assert_eq!(
+        "#![enable(const_expressions)] [200 + 100, 1 << 70, 2 * 0.5, -1 - 127]".parse(),
+        Ok(Value::Seq(vec![
+            Value::Number(Number::new(300.0)),
+            Value::Number(Number::new((1u128 << 70) as f64)),
+            Value::Number(Number::new(1.0)),
+            Value::Number(Number::new(-128.0)),
+        ]))
+    );
+
+    // a leading parenthesis starts an expression rather than a tuple
+    assert_eq!(
+        "#![enable(const_expressions)] (1 + 2) * 3".parse(),
+        Ok(Value::Number(Number::new(9.0)))
+    );
+    assert_eq!(
+        "#![enable(const_expressions)] [(1 + 2) * 3, (4), (5, 6)]".parse(),
+        Ok(Value::Seq(vec![
+            Value::Number(Number::new(9.0)),
+            Value::Number(Number::new(4.0)),
+            Value::Seq(vec![
+                Value::Number(Number::new(5.0)),
+                Value::Number(Number::new(6.0)),
+            ]),
+        ]))
+    );
+    assert_eq!(
+        eval::<Vec<i64>>("#![enable(const_expressions)] [(1 + 2) * 3, (4)]"),
+        Ok(vec![9, 4])
+    );
+    assert_eq!(
+        eval::<Vec<(u8, u8)>>("#![enable(const_expressions)] [((1) + 1, 3)]"),
+        Ok(vec![(2, 3)])
+    );
+
+    // `u128` values past `i128::MAX`
+    assert_eq!(
+        eval::<u128>(&format!(
+            "#![enable(const_expressions)] {} + 1",
+            u128::MAX - 1
+        )),
+        Ok(u128::MAX)
+    );
+}
+
+#[test]
+fn test_suffixes() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(untagged)]
+    enum Untagged {
+        U8(u8),
+        U16(u16),
+    }
+
+    assert_eq!(
+        eval::<Untagged>("#![enable(const_expressions, number_suffixes)] 2 + 3"),
+        Ok(Untagged::U8(5))
+    );
+    // a suffix gives the type of the whole expression
+    assert_eq!(
+        eval::<Untagged>("#![enable(const_expressions, number_suffixes)] 200 + 100u8")
+            .map_err(|e| e.code),
+        Err(ErrorCode::IntegerOutOfBounds)
+    );
+    assert_eq!(
+        eval::<u8>("#![enable(const_expressions, number_suffixes)] 2 + 3u16").map_err(|e| e.code),
+        Err(ErrorCode::InvalidNumberSuffix {
+            expected: "u8",
+            found: "u16",
+        })
+    );
+    assert_eq!(
+        eval::<Untagged>("#![enable(const_expressions, number_suffixes)] 2u8 + 3u16")
+            .map_err(|e| e.code),
+        Err(ErrorCode::InvalidNumberSuffix {
+            expected: "u8",
+            found: "u16",
+        })
+    );
+    assert_eq!(
+        eval::<u16>("#![enable(const_expressions, number_suffixes)] 2u16 * 3"),
+        Ok(6)
+    );
+}
@@ -0,0 +1,179 @@
+#![cfg(feature = "datetime")]
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use ron::{
+    de::from_str,
+    extensions::Extensions,
+    ser::{to_string_pretty, PrettyConfig},
+    value::{Map, Number, Value},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Release {
+    created: DateTime<Utc>,
+    day: NaiveDate,
+    at: NaiveTime,
+    local: NaiveDateTime,
+}
+
+#[test]
+fn test_chrono() {
+    let release = Release {
+        created: "2024-05-01T12:00:00Z".parse().unwrap(),
+        day: "2024-05-01".parse().unwrap(),
+        at: "12:30:15.5".parse().unwrap(),
+        local: "2024-05-01T12:00:00".parse().unwrap(),
+    };
+
+    assert_eq!(
+        from_str::<Release>(
+            "#![enable(datetime_literals)]
+            (
+                created: 2024-05-01T12:00:00Z,
+                day: 2024-05-01,
+                at: 12:30:15.5,
+                local: 2024-05-01T12:00:00,
+            )"
+        ),
+        Ok(release)
+    );
+
+    // quoted strings still work
+    assert!(from_str::<Release>(
+        "#![enable(datetime_literals)]
+        (
+            created: \"2024-05-01T12:00:00+02:00\",
+            day: \"2024-05-01\",
+            at: \"12:30:15\",
+            local: \"2024-05-01T12:00:00\",
+        )"
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_literals() {
+    let lit = |s: &str| from_str::<String>(&format!("#![enable(datetime_literals)] {}", s));
+
+    assert_eq!(lit("2024-05-01"), Ok("2024-05-01".to_owned()));
+    assert_eq!(lit("12:00:00"), Ok("12:00:00".to_owned()));
+    assert_eq!(
+        lit("2024-05-01t12:00:00.123456z"),
+        Ok("2024-05-01t12:00:00.123456z".to_owned())
+    );
+    assert_eq!(
+        lit("1985-04-12T23:20:50.52-08:00"),
+        Ok("1985-04-12T23:20:50.52-08:00".to_owned())
+    );
+
+    assert!(lit("2024-05-01T12:00").is_err());
+    assert!(lit("2024-05-01x").is_err());
+    assert!(lit("12:00:00.").is_err());
+    assert!(lit("12:00:00+02").is_err());
+
+    // without the extension, a literal is not a string
+    assert!(from_str::<String>("2024-05-01").is_err());
+}
+
+#[test]
+fn test_value() {
+    assert_eq!(
+        "#![enable(datetime_literals)] [2024-05-01, \"2024-05-01\", 2024]".parse(),
+        Ok(Value::Seq(vec![
+            Value::DateTime("2024-05-01".to_owned()),
+            Value::String("2024-05-01".to_owned()),
+            Value::Number(Number::new(2024.0)),
+        ]))
+    );
+
+    let mut map = Map::new();
+    map.insert(
+        Value::String("created".to_owned()),
+        Value::DateTime("2024-05-01T12:00:00Z".to_owned()),
+    );
+    let value = Value::Map(map);
+
+    assert_eq!(
+        "#![enable(datetime_literals)] { \"created\": 2024-05-01T12:00:00Z }".parse(),
+        Ok(value.clone())
+    );
+
+    let created: DateTime<Utc> = Value::DateTime("2024-05-01T12:00:00Z".to_owned())
+        .into_rust()
+        .unwrap();
+    assert_eq!(
+        created,
+        "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap()
+    );
+
+    let value = Value::Seq(vec![
+        Value::DateTime("2024-05-01".to_owned()),
+        Value::String("2024-05-01".to_owned()),
+    ]);
+    let pretty = PrettyConfig::new()
+        .with_extensions(Extensions::DATETIME_LITERALS)
+        .with_max_width(80);
+    assert_eq!(
+        to_string_pretty(&value, pretty),
+        Ok("#![enable(datetime_literals)]\n[2024-05-01, \"2024-05-01\"]".to_owned())
+    );
+
+    // a `Value::DateTime` becomes a string without the extension
+    assert_eq!(
+        to_string_pretty(&value, PrettyConfig::new().with_max_width(80)),
+        Ok("[\"2024-05-01\", \"2024-05-01\"]".to_owned())
+    );
+}
+
+#[test]
+fn test_buffered() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(untagged)]
+    enum Untagged {
+        N(u32),
+        S(String),
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(tag = "kind")]
+    enum Tagged {
+        Event { at: String },
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Flattened {
+        name: String,
+        #[serde(flatten)]
+        rest: std::collections::BTreeMap<String, String>,
+    }
+
+    assert_eq!(
+        from_str("#![enable(datetime_literals)] 2024-05-01"),
+        Ok(Untagged::S("2024-05-01".to_owned()))
+    );
+    assert_eq!(
+        from_str("#![enable(datetime_literals)] (kind: \"Event\", at: 12:00:00)"),
+        Ok(Tagged::Event {
+            at: "12:00:00".to_owned()
+        })
+    );
+    assert_eq!(
+        from_str("#![enable(datetime_literals)] {\"name\": \"a\", \"created\": 2024-05-01}"),
+        Ok(Flattened {
+            name: "a".to_owned(),
+            rest: vec![("created".to_owned(), "2024-05-01".to_owned())]
+                .into_iter()
+                .collect(),
+        })
+    );
+
+    // values within a `Value` are still told apart from strings
+    assert_eq!(
+        "#![enable(datetime_literals, anchors)] [Some(&d 2024-05-01), *d]".parse(),
+        Ok(Value::Seq(vec![
+            Value::Option(Some(Box::new(Value::DateTime("2024-05-01".to_owned())))),
+            Value::DateTime("2024-05-01".to_owned()),
+        ]))
+    );
+}
@@ -65,3 +65,45 @@ fn test_chars() {
 fn test_nul_in_string() {
     check_same("Hello\0World!".to_owned());
 }
+
+#[test]
+fn test_raw_strings() {
+    use ron::ser::{to_string_pretty, PrettyConfig};
+
+    let pretty =
+        |s: &str| to_string_pretty(&s, PrettyConfig::new().with_raw_strings(true)).unwrap();
+
+    assert_eq!(pretty("plain"), "\"plain\"");
+    assert_eq!(pretty("C:\\Users"), "r\"C:\\Users\"");
+    assert_eq!(pretty("say \"hi\""), "r#\"say \"hi\"\"#");
+    assert_eq!(pretty("\"##\" and \"#"), "r###\"\"##\" and \"#\"###");
+
+    for s in &["C:\\Users", "say \"hi\"", "\"##\" and \"#", "two\nlines"] {
+        assert_eq!(from_str::<String>(&pretty(s)).as_deref(), Ok(*s));
+    }
+}
+
+#[test]
+fn test_escape_non_ascii() {
+    use ron::ser::{to_string_pretty, PrettyConfig};
+
+    let config = PrettyConfig::new()
+        .with_escape_non_ascii(true)
+        .with_raw_strings(true);
+    let value = ("grüße\\", '♠', 'a');
+    let serial = to_string_pretty(&value, config).unwrap();
+
+    assert_eq!(serial, "(\"gr\\u{fc}\\u{df}e\\\\\", '\\u{2660}', 'a')");
+    assert_eq!(from_str(&serial), Ok(("grüße\\".to_owned(), '♠', 'a')));
+}
+
+#[test]
+fn test_rust_escapes() {
+    assert_eq!(from_str::<String>("\"a\\0b\""), Ok("a\0b".to_owned()));
+    assert_eq!(from_str::<char>("'\\0'"), Ok('\0'));
+    assert_eq!(
+        from_str::<String>("\"one \\\n      two \\\r\n\tthree\""),
+        Ok("one two three".to_owned())
+    );
+    assert!(from_str::<char>("'\\\n'").is_err());
+}
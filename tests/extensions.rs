@@ -79,3 +79,499 @@ fn implicit_some() {
 
     println!("implicit_some: {:#?}", d);
 }
+
+#[test]
+fn serialize_unwrap_newtypes() {
+    use ron::{extensions::Extensions, ser::PrettyConfig};
+
+    let value = Struct {
+        tuple: ((), NewType(0.5), TupleStruct(UnitStruct, -5)),
+        vec: vec![None, Some(UnitStruct)],
+        map: vec![(Key(7), Enum::Bool(true))].into_iter().collect(),
+    };
+    let config = PrettyConfig::new()
+        .with_new_line("\n".to_owned())
+        .with_extensions(Extensions::UNWRAP_NEWTYPES | Extensions::IMPLICIT_SOME);
+    let serial = ron::ser::to_string_pretty(&value, config).unwrap();
+
+    assert_eq!(
+        serial,
+        "#![enable(unwrap_newtypes, implicit_some)]
+(
+    tuple: ((), 0.5, ((), -5)),
+    vec: [
+        None,
+        (),
+    ],
+    map: {
+        7: Bool(true),
+    },
+)"
+    );
+    assert_eq!(ron::de::from_str(&serial), Ok(value));
+}
+
+#[test]
+fn unquoted_map_keys() {
+    use ron::{extensions::Extensions, ser::PrettyConfig};
+    use std::collections::BTreeMap;
+
+    let map: BTreeMap<String, (String, i32)> = ron::de::from_str(
+        "#![enable(unquoted_map_keys)]
+        { background: (\"bg\", 0), \"status-bar\": (\"sb\", 1), r#type: (\"ty\", 2) }",
+    )
+    .unwrap();
+    assert_eq!(map["background"], ("bg".to_owned(), 0));
+    assert_eq!(map["status-bar"], ("sb".to_owned(), 1));
+    assert_eq!(map["type"], ("ty".to_owned(), 2));
+
+    assert!(ron::de::from_str::<BTreeMap<String, i32>>("{ background: 0 }").is_err());
+
+    let config = PrettyConfig::new()
+        .with_new_line("\n".to_owned())
+        .with_extensions(Extensions::UNQUOTED_MAP_KEYS);
+    let serial = ron::ser::to_string_pretty(&map, config).unwrap();
+
+    assert_eq!(
+        serial,
+        "#![enable(unquoted_map_keys)]
+{
+    background: (\"bg\", 0),
+    \"status-bar\": (\"sb\", 1),
+    type: (\"ty\", 2),
+}"
+    );
+    assert_eq!(ron::de::from_str(&serial), Ok(map.clone()));
+
+    // self-describing consumers see bare keys as strings too
+    let value: ron::Value = ron::de::from_str(&serial).unwrap();
+    assert_eq!(value.into_rust(), Ok(map));
+    let values: BTreeMap<ron::Value, i32> =
+        ron::de::from_str("#![enable(unquoted_map_keys)] { foo: 1 }").unwrap();
+    assert_eq!(
+        values.into_iter().collect::<Vec<_>>(),
+        vec![(ron::Value::String("foo".to_owned()), 1)]
+    );
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(untagged)]
+    enum Untagged {
+        Map(BTreeMap<String, i32>),
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Flattened {
+        foo: i32,
+        #[serde(flatten)]
+        rest: BTreeMap<String, i32>,
+    }
+
+    assert_eq!(
+        ron::de::from_str("#![enable(unquoted_map_keys)] { foo: 1 }"),
+        Ok(Untagged::Map(
+            vec![("foo".to_owned(), 1)].into_iter().collect()
+        ))
+    );
+    assert_eq!(
+        ron::de::from_str("#![enable(unquoted_map_keys)] { foo: 1, bar: 2 }"),
+        Ok(Flattened {
+            foo: 1,
+            rest: vec![("bar".to_owned(), 2)].into_iter().collect(),
+        })
+    );
+
+    // keywords stay quoted, so they keep their meaning for `Value`
+    let keywords: BTreeMap<String, i32> = ["true", "None", "NaN", "inf"]
+        .iter()
+        .map(|key| (key.to_string(), 0))
+        .collect();
+    let config = PrettyConfig::new().with_extensions(Extensions::UNQUOTED_MAP_KEYS);
+    let serial = ron::ser::to_string_pretty(&keywords, config).unwrap();
+
+    assert!(serial.contains("\"true\": 0"));
+    assert!(serial.contains("\"NaN\": 0"));
+    assert_eq!(ron::de::from_str(&serial), Ok(keywords));
+    assert!(ron::de::from_str::<BTreeMap<String, i32>>(
+        "#![enable(unquoted_map_keys)] { true: 0 }"
+    )
+    .is_err());
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Spawn {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+enum Command {
+    Spawn(Spawn),
+    Move((i32, i32)),
+    Wait(u32),
+    Nested(Option<Box<Command>>),
+    Then(Box<Command>),
+    Jump { height: u32 },
+}
+
+#[test]
+fn unwrap_variant_newtypes() {
+    use ron::{extensions::Extensions, ser::PrettyConfig};
+
+    let commands: Vec<Command> = ron::de::from_str(
+        "#![enable(unwrap_variant_newtypes)]
+        [Spawn(x: 1, y: 2), Move(3, 4,), Wait(5), Nested(Some(Spawn(x: 6, y: 7))), Then(Jump(height: 8))]",
+    )
+    .unwrap();
+    assert_eq!(
+        commands,
+        vec![
+            Command::Spawn(Spawn { x: 1, y: 2 }),
+            Command::Move((3, 4)),
+            Command::Wait(5),
+            Command::Nested(Some(Box::new(Command::Spawn(Spawn { x: 6, y: 7 })))),
+            Command::Then(Box::new(Command::Jump { height: 8 })),
+        ]
+    );
+
+    assert!(ron::de::from_str::<Command>("Spawn(x: 1, y: 2)").is_err());
+
+    let config = PrettyConfig::new()
+        .with_depth_limit(0)
+        .with_extensions(Extensions::UNWRAP_VARIANT_NEWTYPES);
+    let serial = ron::ser::to_string_pretty(&commands, config).unwrap();
+
+    assert_eq!(
+        serial,
+        "#![enable(unwrap_variant_newtypes)]
+[Spawn(x:1,y:2,),Move(3,4,),Wait(5),Nested(Some(Spawn(x:6,y:7,))),Then(Jump(height:8,)),]"
+    );
+    assert_eq!(ron::de::from_str(&serial), Ok(commands));
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+enum Shape {
+    Point,
+    Circle(f32),
+    Rect(f32, f32),
+    Polygon { sides: u8 },
+}
+
+#[test]
+fn variant_paths() {
+    use ron::{extensions::Extensions, ser::PrettyConfig, value::Value};
+
+    let shapes: Vec<Shape> = ron::de::from_str(
+        "#![enable(variant_paths)]
+        [Shape::Point, Shape::Circle(2.0), Rect(1.0, 3.0), Shape::Polygon(sides: 5)]",
+    )
+    .unwrap();
+    assert_eq!(
+        shapes,
+        vec![
+            Shape::Point,
+            Shape::Circle(2.0),
+            Shape::Rect(1.0, 3.0),
+            Shape::Polygon { sides: 5 },
+        ]
+    );
+
+    let err = ron::de::from_str::<Shape>("#![enable(variant_paths)] Color::Point").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "1:27: Expected a variant of `Shape`, found `Color::`"
+    );
+    assert!(ron::de::from_str::<Shape>("Shape::Point").is_err());
+
+    assert_eq!(
+        ron::de::from_str::<Value>("#![enable(variant_paths)] [Shape::Circle(2.0)]"),
+        ron::de::from_str::<Value>("[Circle(2.0)]")
+    );
+
+    let config = PrettyConfig::new()
+        .with_depth_limit(0)
+        .with_extensions(Extensions::VARIANT_PATHS);
+    let serial = ron::ser::to_string_pretty(&shapes, config).unwrap();
+
+    assert_eq!(
+        serial,
+        "#![enable(variant_paths)]
+[Shape::Point,Shape::Circle(2.0),Shape::Rect(1.0,3.0,),Shape::Polygon(sides:5,),]"
+    );
+    assert_eq!(ron::de::from_str(&serial), Ok(shapes));
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Fixture {
+    name: String,
+    position: Position,
+    tags: Vec<char>,
+    parent: Option<Box<Fixture>>,
+    shape: Shape,
+    weights: HashMap<String, f32>,
+    id: Id,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Id(u64);
+
+#[test]
+fn braced_structs() {
+    use ron::value::Value;
+
+    let fixture = Fixture {
+        name: "root \"node\"".to_owned(),
+        position: Position { x: -1, y: 2 },
+        tags: vec!['a', '\''],
+        parent: Some(Box::new(Fixture {
+            name: String::new(),
+            position: Position { x: 0, y: 0 },
+            tags: vec![],
+            parent: None,
+            shape: Shape::Point,
+            weights: HashMap::new(),
+            id: Id(0),
+        })),
+        shape: Shape::Polygon { sides: 3 },
+        weights: vec![("a".to_owned(), 0.5)].into_iter().collect(),
+        id: Id(7),
+    };
+
+    for debug in &[format!("{:?}", fixture), format!("{:#?}", fixture)] {
+        let ron = format!("#![enable(braced_structs)] {}", debug);
+
+        assert_eq!(ron::de::from_str(&ron).as_ref(), Ok(&fixture));
+        assert!(ron::de::from_str::<Value>(&ron).is_ok());
+    }
+
+    assert!(ron::de::from_str::<Position>("Position { x: 1, y: 2 }").is_err());
+    assert!(ron::de::from_str::<Option<i32>>("Some(5,)").is_err());
+    assert_eq!(
+        ron::de::from_str::<Option<i32>>("#![enable(braced_structs)] Some(5,)"),
+        Ok(Some(5))
+    );
+    assert!(ron::de::from_str::<Position>("#![enable(braced_structs)] { x: 1, y: 2 }").is_err());
+    assert_eq!(
+        ron::de::from_str::<Value>("#![enable(braced_structs)] Position { x: 1, y: 2 }"),
+        ron::de::from_str::<Value>("(x: 1, y: 2)")
+    );
+}
+
+#[test]
+fn array_repetition() {
+    use ron::value::Value;
+
+    let table: Vec<u8> = ron::de::from_str("#![enable(array_repetition)] [0; 256]").unwrap();
+    assert_eq!(table, vec![0; 256]);
+
+    let grid: [(i32, i32); 16] =
+        ron::de::from_str("#![enable(array_repetition)] [ (1, -1) ; 16 ]").unwrap();
+    assert_eq!(grid, [(1, -1); 16]);
+
+    let shapes: Vec<Vec<Shape>> =
+        ron::de::from_str("#![enable(array_repetition)] [[Circle(0.5); 2]; 3]").unwrap();
+    assert_eq!(shapes.len(), 3);
+    assert!(shapes
+        .iter()
+        .all(|row| row[..] == [Shape::Circle(0.5), Shape::Circle(0.5)]));
+
+    assert_eq!(
+        ron::de::from_str::<Vec<u8>>("#![enable(array_repetition)] [1, 2; 3]").map_err(|e| e.code),
+        ron::de::from_str::<Vec<u8>>("[1, 2; 3]").map_err(|e| e.code)
+    );
+    assert_eq!(
+        ron::de::from_str::<Vec<u8>>("#![enable(array_repetition)] [1, 2]"),
+        Ok(vec![1, 2])
+    );
+    assert!(ron::de::from_str::<Vec<u8>>("[0; 4]").is_err());
+
+    assert_eq!(
+        ron::de::from_str::<Value>("#![enable(array_repetition)] [\"a\"; 2]"),
+        ron::de::from_str::<Value>("[\"a\", \"a\"]")
+    );
+
+    // tuples can be written as a repetition only with square brackets
+    assert_eq!(
+        ron::de::from_str("#![enable(array_repetition)] [7; 2]"),
+        Ok((7u8, 7u8))
+    );
+    assert!(ron::de::from_str::<(u8, u8)>("#![enable(array_repetition)] (7; 2)").is_err());
+
+    assert_eq!(
+        ron::de::from_str::<Vec<u8>>("#![enable(array_repetition)] [1 2; 3]").map_err(|e| e.code),
+        Err(ron::error::ErrorCode::ExpectedComma)
+    );
+    assert!(ron::de::from_str::<Vec<u8>>("#![enable(array_repetition)] [-; 0]").is_err());
+    assert_eq!(
+        ron::de::from_str::<Vec<u8>>("#![enable(array_repetition)] [1; 0]"),
+        Ok(vec![])
+    );
+}
+
+#[test]
+fn array_repetition_limits() {
+    use ron::value::Value;
+
+    // the look-ahead over nested lists does not parse them
+    let depth = 40;
+    let nested = format!(
+        "#![enable(array_repetition)] {}0{}",
+        "[".repeat(depth),
+        "; 1]".repeat(depth)
+    );
+    assert!(ron::de::from_str::<Value>(&nested).is_ok());
+
+    let grid: Vec<Vec<u8>> =
+        ron::de::from_str("#![enable(array_repetition)] [[0; 300]; 300]").unwrap();
+    assert_eq!(grid.len() * grid[0].len(), 90_000);
+
+    assert_eq!(
+        ron::de::from_str::<Value>("#![enable(array_repetition)] [[[0; 300]; 300]; 300]")
+            .map_err(|e| e.code),
+        Err(ron::error::ErrorCode::RepetitionLimitExceeded)
+    );
+    assert_eq!(
+        ron::de::from_str::<Vec<u8>>("#![enable(array_repetition)] [0; 18446744073709551615]")
+            .map_err(|e| e.code),
+        Err(ron::error::ErrorCode::RepetitionLimitExceeded)
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Settings {
+    title: String,
+    size: (u32, u32),
+    window: Position,
+    tags: Vec<String>,
+}
+
+#[test]
+fn implicit_outer_struct() {
+    use ron::{extensions::Extensions, ser::PrettyConfig, value::Value};
+
+    let settings = Settings {
+        title: "Game".to_owned(),
+        size: (800, 600),
+        window: Position { x: 1, y: 2 },
+        tags: vec![],
+    };
+
+    let doc = "#![enable(implicit_outer_struct)]
+title: \"Game\",
+size: (800, 600),
+window: (x: 1, y: 2),
+tags: []
+";
+    assert_eq!(ron::de::from_str(doc).as_ref(), Ok(&settings));
+    assert_eq!(
+        ron::de::from_str::<Value>(doc),
+        ron::de::from_str::<Value>(
+            "(title: \"Game\", size: (800, 600), window: (x: 1, y: 2), tags: [])"
+        )
+    );
+    assert_eq!(
+        ron::de::from_str::<Settings>(
+            "#![enable(implicit_outer_struct)] (title: \"Game\", size: (800, 600), window: (x: 1, y: 2), tags: [])"
+        )
+        .as_ref(),
+        Ok(&settings)
+    );
+    assert!(ron::de::from_str::<Settings>("title: \"Game\"").is_err());
+    assert!(ron::de::from_str::<Position>("#![enable(implicit_outer_struct)] x: 1 y: 2").is_err());
+
+    let config = PrettyConfig::new()
+        .with_new_line("\n".to_owned())
+        .with_max_width(40)
+        .with_extensions(Extensions::IMPLICIT_OUTER_STRUCT);
+    let serial = ron::ser::to_string_pretty(&settings, config).unwrap();
+
+    assert_eq!(
+        serial,
+        "#![enable(implicit_outer_struct)]
+title: \"Game\",
+size: (800, 600),
+window: (x: 1, y: 2),
+tags: [],
+"
+    );
+    assert_eq!(ron::de::from_str(&serial).as_ref(), Ok(&settings));
+
+    // only the outer struct is written without parentheses beyond the depth limit
+    let config = PrettyConfig::new()
+        .with_depth_limit(0)
+        .with_extensions(Extensions::IMPLICIT_OUTER_STRUCT);
+    let serial = ron::ser::to_string_pretty(&settings, config).unwrap();
+
+    assert_eq!(
+        serial,
+        "#![enable(implicit_outer_struct)]\ntitle:\"Game\",size:(800,600,),window:(x:1,y:2,),tags:[],"
+    );
+    assert_eq!(ron::de::from_str(&serial), Ok(settings));
+}
+
+#[test]
+fn indented_strings() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Item {
+        name: String,
+        description: String,
+        note: String,
+    }
+
+    let item: Item = ron::de::from_str(
+        "#![enable(indented_strings)]
+        (
+            name: \"  Sword  \",
+            description: \"
+                A blade of \\\"fine\\\" steel.
+
+                  Deals extra damage
+                \",
+            note: r#\"
+                raw \\n text
+                  kept\"#,
+        )",
+    )
+    .unwrap();
+
+    assert_eq!(
+        item,
+        Item {
+            name: "  Sword  ".to_owned(),
+            description: "A blade of \"fine\" steel.\n\n  Deals extra damage".to_owned(),
+            note: "raw \\n text\n  kept".to_owned(),
+        }
+    );
+
+    let plain: String = ron::de::from_str("\"\n    kept\n\"").unwrap();
+    assert_eq!(plain, "\n    kept\n");
+
+    // escaped line breaks and whitespace do not change the indentation
+    assert_eq!(
+        ron::de::from_str::<String>("#![enable(indented_strings)] \"\n    a\\n  b\n    c\n\""),
+        Ok("a\n  b\nc".to_owned())
+    );
+    assert_eq!(
+        ron::de::from_str::<String>("#![enable(indented_strings)] \"\n    a\n    \\t\""),
+        Ok("a\n\t".to_owned())
+    );
+    assert_eq!(
+        ron::de::from_str::<String>("#![enable(indented_strings)] \"\\n  a\n  b\""),
+        Ok("\n  a\n  b".to_owned())
+    );
+
+    // raw strings would be unindented, so leading line breaks are escaped
+    let config = ron::ser::PrettyConfig::new()
+        .with_raw_strings(true)
+        .with_extensions(ron::extensions::Extensions::INDENTED_STRINGS);
+    let strings = vec!["\n  a\n  b\n".to_owned(), "a\n  b\n".to_owned()];
+    let serial = ron::ser::to_string_pretty(&strings, config).unwrap();
+    assert!(serial.contains("\"\\n  a\\n  b\\n\""));
+    assert!(serial.contains("r\"a\n  b\n\""));
+    assert_eq!(ron::de::from_str(&serial), Ok(strings));
+}
@@ -1,4 +1,8 @@
-use ron::de::from_str;
+use ron::{
+    de::from_str,
+    ser::{to_string, to_string_pretty, PrettyConfig},
+    value::{Number, Value},
+};
 
 #[test]
 fn test_inf_and_nan() {
@@ -6,3 +10,20 @@ fn test_inf_and_nan() {
     assert_eq!(from_str("-inf"), Ok(std::f64::NEG_INFINITY));
     assert_eq!(from_str::<f64>("NaN").map(|n| n.is_nan()), Ok(true))
 }
+
+#[test]
+fn test_float_roundtrip() {
+    let config = PrettyConfig::new().with_float_scientific_threshold(3);
+
+    for &v in &[4.0, 0.1 + 0.2, -1e-300, 1.7976931348623157e308, 5e-324] {
+        let compact = to_string(&v).unwrap();
+        let pretty = to_string_pretty(&v, config.clone()).unwrap();
+
+        assert_eq!(from_str::<f64>(&compact), Ok(v));
+        assert_eq!(from_str::<f64>(&pretty), Ok(v));
+        assert_eq!(
+            from_str::<Value>(&compact),
+            Ok(Value::Number(Number::new(v)))
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use std::{collections::HashMap, fs, io};
+
+use ron::{
+    de::{from_str, from_str_with_includes, FileResolver, Includes, Resolver},
+    error::{Error, ErrorCode, Position},
+};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Enemy {
+    Slime { hp: u32 },
+    Bat,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Level {
+    name: String,
+    enemies: Vec<Enemy>,
+    boss: Enemy,
+}
+
+/// Resolves includes from a map of file names to content, counting the
+/// files it is asked for.
+struct Files {
+    files: HashMap<String, String>,
+    resolved: usize,
+}
+
+impl Files {
+    fn new(files: &[(&str, &str)]) -> Self {
+        Files {
+            files: files
+                .iter()
+                .map(|(name, content)| ((*name).to_owned(), (*content).to_owned()))
+                .collect(),
+            resolved: 0,
+        }
+    }
+}
+
+impl Resolver for Files {
+    fn resolve(&mut self, _from: Option<&str>, path: &str) -> io::Result<(String, String)> {
+        self.resolved += 1;
+
+        match self.files.get(path) {
+            Some(content) => Ok((path.to_owned(), content.clone())),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "no such file")),
+        }
+    }
+}
+
+fn load<'a, T: Deserialize<'a>>(
+    input: &'a str,
+    includes: &'a mut Option<Includes>,
+    files: &mut Files,
+) -> Result<T, Error> {
+    let includes = includes.insert(Includes::load(input, files)?);
+
+    from_str_with_includes(input, includes)
+}
+
+#[test]
+fn test_include() {
+    let mut files = Files::new(&[
+        ("enemies.ron", "[Slime(hp: 10), include!(\"bat.ron\"), Bat]"),
+        ("bat.ron", "// a bat\nBat"),
+        ("boss.ron", "#![enable(implicit_some)] Slime(hp: 500)"),
+    ]);
+    let input = "#![enable(include)]
+        (
+            name: \"Cave\",
+            enemies: include!(\"enemies.ron\"),
+            boss: include!( \"boss.ron\" ),
+        )";
+
+    assert_eq!(
+        load(input, &mut None, &mut files),
+        Ok(Level {
+            name: "Cave".to_owned(),
+            enemies: vec![Enemy::Slime { hp: 10 }, Enemy::Bat, Enemy::Bat],
+            boss: Enemy::Slime { hp: 500 },
+        })
+    );
+    assert_eq!(files.resolved, 3);
+
+    // a file included twice is loaded once
+    let mut files = Files::new(&[("bat.ron", "Bat")]);
+    assert_eq!(
+        load(
+            "#![enable(include)] [include!(\"bat.ron\"), include!(\"bat.ron\")]",
+            &mut None,
+            &mut files,
+        ),
+        Ok(vec![Enemy::Bat, Enemy::Bat])
+    );
+    assert_eq!(files.resolved, 1);
+
+    // directives in strings and comments are not loaded
+    let mut files = Files::new(&[]);
+    assert_eq!(
+        load(
+            "#![enable(include)] /* include!(\"a.ron\") */ \"include!(\\\"a.ron\\\")\"",
+            &mut None,
+            &mut files,
+        ),
+        Ok("include!(\"a.ron\")".to_owned())
+    );
+    assert_eq!(files.resolved, 0);
+}
+
+#[test]
+fn test_errors() {
+    let mut files = Files::new(&[
+        ("bad.ron", "Slime(\n    hp: -1,\n)"),
+        ("trailing.ron", "Bat Bat"),
+    ]);
+
+    // errors in an included file refer to that file
+    let mut includes = None;
+    let err = load::<Enemy>(
+        "#![enable(include)] include!(\"bad.ron\")",
+        &mut includes,
+        &mut files,
+    )
+    .unwrap_err();
+    let file = err.position.file;
+    assert_eq!(
+        err,
+        Error {
+            code: ErrorCode::ExpectedInteger,
+            position: Position {
+                line: 2,
+                col: 9,
+                file,
+            },
+        }
+    );
+    assert_eq!(
+        file.and_then(|file| includes.as_ref()?.file_name(file)),
+        Some("bad.ron")
+    );
+    assert_eq!(err.to_string(), "2:9 in included file 1: Expected integer");
+    assert_eq!(
+        includes.as_ref().map(|includes| includes.describe(&err)),
+        Some("bad.ron:2:9: Expected integer".to_owned())
+    );
+
+    assert_eq!(
+        load::<Enemy>(
+            "#![enable(include)] include!(\"trailing.ron\")",
+            &mut None,
+            &mut files,
+        )
+        .map_err(|e| e.code),
+        Err(ErrorCode::TrailingCharacters)
+    );
+
+    assert_eq!(
+        load::<Vec<Enemy>>(
+            "#![enable(include)]\n[Bat, include!(\"missing.ron\")]",
+            &mut None,
+            &mut files,
+        ),
+        Err(Error {
+            code: ErrorCode::IncludeFailed {
+                path: "missing.ron".to_owned(),
+                error: "no such file".to_owned(),
+            },
+            position: Position {
+                line: 2,
+                col: 7,
+                file: None,
+            },
+        })
+    );
+
+    assert_eq!(
+        load::<Enemy>(
+            "#![enable(include)] include!(bad.ron)",
+            &mut None,
+            &mut files
+        )
+        .map_err(|e| e.code),
+        Err(ErrorCode::ExpectedString)
+    );
+    assert_eq!(
+        load::<Enemy>(
+            "#![enable(include)] include!(\"bad.ron\"",
+            &mut None,
+            &mut files
+        )
+        .map_err(|e| e.code),
+        Err(ErrorCode::ExpectedInclude)
+    );
+
+    // the files have to be loaded first
+    assert_eq!(
+        from_str::<Enemy>("#![enable(include)] include!(\"bad.ron\")").map_err(|e| e.code),
+        Err(ErrorCode::IncludeFailed {
+            path: "bad.ron".to_owned(),
+            error: "not loaded with `Includes::load`".to_owned(),
+        })
+    );
+
+    // without the extension, a directive is not valid
+    assert!(load::<Enemy>("include!(\"bad.ron\")", &mut None, &mut files).is_err());
+}
+
+#[test]
+fn test_cycle() {
+    let mut files = Files::new(&[
+        ("a.ron", "#![enable(include)] [include!(\"b.ron\")]"),
+        ("b.ron", "[Bat, include!(\"a.ron\")]"),
+    ]);
+
+    let err = Includes::load("#![enable(include)] include!(\"a.ron\")", &mut files).unwrap_err();
+    assert_eq!(err.code, ErrorCode::IncludeCycle("a.ron".to_owned()));
+    assert_eq!((err.position.line, err.position.col), (1, 7));
+    assert!(err.position.file.is_some());
+
+    let mut files = Files::new(&[("self.ron", "[include!(\"self.ron\")]")]);
+    assert_eq!(
+        Includes::load("#![enable(include)] include!(\"self.ron\")", &mut files)
+            .err()
+            .map(|e| e.code),
+        Some(ErrorCode::IncludeCycle("self.ron".to_owned()))
+    );
+}
+
+#[test]
+fn test_files() {
+    let root = std::env::temp_dir().join(format!("ron-include-{}", std::process::id()));
+    fs::create_dir_all(root.join("enemies")).unwrap();
+    fs::write(
+        root.join("enemies/all.ron"),
+        "[include!(\"./slime.ron\"), include!(\"../bat.ron\")]",
+    )
+    .unwrap();
+    fs::write(root.join("enemies/slime.ron"), "Slime(hp: 10)").unwrap();
+    fs::write(root.join("bat.ron"), "Bat").unwrap();
+    fs::write(root.join("boss.ron"), "Slime(hp: )").unwrap();
+
+    let input = "#![enable(include)]
+        (
+            name: \"Cave\",
+            enemies: include!(\"enemies/all.ron\"),
+            boss: include!(\"enemies/../enemies/slime.ron\"),
+        )";
+    let includes = Includes::load(input, &mut FileResolver::new(&root));
+    let level = includes
+        .as_ref()
+        .map_err(Clone::clone)
+        .and_then(|includes| from_str_with_includes::<Level>(input, includes));
+
+    let input = "#![enable(include)] include!(\"boss.ron\")";
+    let error = Includes::load(input, &mut FileResolver::new(&root)).map(|includes| {
+        from_str_with_includes::<Enemy>(input, &includes).map_err(|e| {
+            e.position
+                .file
+                .and_then(|file| includes.file_name(file))
+                .map(str::to_owned)
+        })
+    });
+
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(
+        level,
+        Ok(Level {
+            name: "Cave".to_owned(),
+            enemies: vec![Enemy::Slime { hp: 10 }, Enemy::Bat],
+            boss: Enemy::Slime { hp: 10 },
+        })
+    );
+    assert_eq!(
+        error,
+        Ok(Err(Some(
+            root.join("boss.ron").to_string_lossy().into_owned()
+        )))
+    );
+}
+
+#[test]
+fn test_include_limit() {
+    // each file includes the next one twice, doubling the files to replay
+    let mut files = Files {
+        files: (0..16)
+            .map(|i| {
+                let content = match i {
+                    15 => "Bat".to_owned(),
+                    i => format!("[include!(\"{0}.ron\"), include!(\"{0}.ron\")]", i + 1),
+                };
+
+                (format!("{}.ron", i), content)
+            })
+            .collect(),
+        resolved: 0,
+    };
+
+    let input = "#![enable(include)] include!(\"0.ron\")";
+    let includes = Includes::load(input, &mut files).unwrap();
+    assert_eq!(files.resolved, 16);
+    assert_eq!(
+        from_str_with_includes::<ron::Value>(input, &includes).map_err(|e| e.code),
+        Err(ErrorCode::IncludeLimitExceeded)
+    );
+}
@@ -0,0 +1,127 @@
+use ron::{
+    de::from_str,
+    extensions::Extensions,
+    ser::{to_string_pretty, PrettyConfig},
+    value::{Number, Value},
+};
+use serde::Deserialize;
+
+/// The type `deserialize_any` reports a number with.
+#[derive(Debug, PartialEq)]
+enum Typed {
+    U8(u8),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    Other,
+}
+
+impl<'de> Deserialize<'de> for Typed {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TypedVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for TypedVisitor {
+            type Value = Typed;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a number")
+            }
+
+            fn visit_u8<E>(self, v: u8) -> Result<Typed, E> {
+                Ok(Typed::U8(v))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Typed, E> {
+                Ok(Typed::I64(v))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Typed, E> {
+                Ok(Typed::U64(v))
+            }
+
+            fn visit_f32<E>(self, v: f32) -> Result<Typed, E> {
+                Ok(Typed::F32(v))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Typed, E> {
+                Ok(Typed::F64(v))
+            }
+
+            fn visit_i8<E>(self, _: i8) -> Result<Typed, E> {
+                Ok(Typed::Other)
+            }
+        }
+
+        deserializer.deserialize_any(TypedVisitor)
+    }
+}
+
+fn suffixed<T: for<'de> Deserialize<'de>>(ron: &str) -> ron::Result<T> {
+    from_str(&format!("#![enable(number_suffixes)] {}", ron))
+}
+
+#[test]
+fn test_any() {
+    assert_eq!(suffixed("5"), Ok(Typed::U8(5)));
+    assert_eq!(suffixed("5i64"), Ok(Typed::I64(5)));
+    assert_eq!(suffixed("0x10usize"), Ok(Typed::U64(16)));
+    assert_eq!(suffixed("-5"), Ok(Typed::Other));
+    assert_eq!(suffixed("-5isize"), Ok(Typed::I64(-5)));
+    assert_eq!(suffixed("1.0"), Ok(Typed::F32(1.0)));
+    assert_eq!(suffixed("1.0f64"), Ok(Typed::F64(1.0)));
+    assert_eq!(suffixed("-2.5e3f64"), Ok(Typed::F64(-2500.0)));
+    assert_eq!(suffixed("0.1f32"), Ok(Typed::F32(0.1)));
+}
+
+#[test]
+fn test_typed() {
+    assert_eq!(suffixed("[1u16, 2, 3u16]"), Ok(vec![1u16, 2, 3]));
+    assert_eq!(
+        suffixed("(-3i128, 0xffu8, 7usize, 1f32)"),
+        Ok((-3i128, 255u8, 7u64, 1f32))
+    );
+
+    let err = suffixed::<u16>("1u8").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "1:30: Expected a number of type `u16`, found suffix `u8`"
+    );
+    assert!(from_str::<u8>("5u8").is_err());
+    assert!(suffixed::<u8>("5u8x").is_err());
+}
+
+#[test]
+fn test_value() {
+    assert_eq!(
+        suffixed("300u8").map_err(|e| e.code),
+        Err::<Value, _>(ron::error::ErrorCode::IntegerOutOfBounds)
+    );
+    assert!(suffixed::<Value>("1.5u8").is_err());
+    assert_eq!(suffixed("7i128"), Ok(Value::Number(Number::new(7.0))));
+}
+
+#[test]
+fn test_serialize() {
+    let config = PrettyConfig::new().with_extensions(Extensions::NUMBER_SUFFIXES);
+    let value = (5u8, -1i32, 1.5f32, f64::INFINITY, vec![2.0f64]);
+    let serial = to_string_pretty(&value, config).unwrap();
+
+    assert_eq!(
+        serial,
+        "#![enable(number_suffixes)]\n(5u8, -1i32, 1.5f32, inf, [\n    2.0f64,\n])"
+    );
+    assert_eq!(from_str(&serial), Ok(value));
+}
+
+#[test]
+fn test_serialize_precision() {
+    let config = PrettyConfig::new()
+        .with_extensions(Extensions::NUMBER_SUFFIXES)
+        .with_float_precision(0);
+    let value = (1.0f32, 2.0f64, f32::INFINITY);
+    let serial = to_string_pretty(&value, config).unwrap();
+
+    assert_eq!(serial, "#![enable(number_suffixes)]\n(1.f32, 2.f64, inf)");
+    assert_eq!(from_str(&serial), Ok(value));
+}
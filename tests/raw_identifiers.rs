@@ -0,0 +1,105 @@
+use ron::{
+    de::from_str,
+    error::ErrorCode,
+    ser::{to_string, to_string_pretty, PrettyConfig, Serializer},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename = "my-struct")]
+struct MyStruct {
+    #[serde(rename = "type")]
+    kind: u8,
+    #[serde(rename = "my-field")]
+    my_field: u8,
+    #[serde(rename = "2d")]
+    two_d: bool,
+    variant: MyEnum,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+enum MyEnum {
+    #[serde(rename = "version-1.0+")]
+    Version,
+}
+
+#[test]
+fn roundtrip_raw_identifiers() {
+    let value = MyStruct {
+        kind: 1,
+        my_field: 2,
+        two_d: true,
+        variant: MyEnum::Version,
+    };
+
+    let mut ser = Serializer::new(None, true);
+    value.serialize(&mut ser).unwrap();
+    let serial = ser.into_output_string();
+
+    assert_eq!(
+        serial,
+        "r#my-struct(type:1,r#my-field:2,r#2d:true,variant:r#version-1.0+,)"
+    );
+    assert_eq!(from_str(&serial), Ok(value));
+
+    let pretty =
+        to_string_pretty(&from_str::<MyStruct>(&serial).unwrap(), PrettyConfig::new()).unwrap();
+    assert_eq!(from_str::<MyStruct>(&pretty).unwrap().my_field, 2);
+
+    assert_eq!(
+        from_str("(r#type: 1, r#my-field: 2, r#2d: false, r#variant: r#version-1.0+)"),
+        Ok(MyStruct {
+            kind: 1,
+            my_field: 2,
+            two_d: false,
+            variant: MyEnum::Version,
+        })
+    );
+}
+
+#[test]
+fn escaped_identifier() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Spaced {
+        #[serde(rename = "with space")]
+        field: u8,
+        #[serde(rename = "a\"b")]
+        quoted: u8,
+    }
+
+    let spaced = Spaced {
+        field: 0,
+        quoted: 1,
+    };
+    let ron = to_string(&spaced).unwrap();
+
+    assert_eq!(ron, "(r#with\\u{20}space:0,r#a\\u{22}b:1,)");
+    assert_eq!(from_str(&ron), Ok(spaced));
+    assert_eq!(
+        from_str("(r#with\\x20space: 0, r#a\\\"b: 1)"),
+        Ok(Spaced {
+            field: 0,
+            quoted: 1,
+        })
+    );
+}
+
+#[test]
+fn invalid_identifier() {
+    #[derive(Serialize)]
+    struct Empty {
+        #[serde(rename = "")]
+        field: u8,
+    }
+
+    assert_eq!(
+        to_string(&Empty { field: 0 }).map_err(|e| e.code),
+        Err(ErrorCode::InvalidIdentifier(String::new()))
+    );
+}
+
+#[test]
+fn raw_strings_are_not_identifiers() {
+    assert_eq!(from_str::<String>("r#\"raw\"#"), Ok("raw".to_owned()));
+    assert_eq!(from_str::<String>("r\"raw\""), Ok("raw".to_owned()));
+}
@@ -0,0 +1,166 @@
+use std::collections::{BTreeSet, HashSet};
+
+use ron::{
+    de::from_str,
+    error::{Error, ErrorCode, Position},
+    extensions::Extensions,
+    ser::{to_string_pretty, PrettyConfig},
+    value::{Number, Value},
+};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Tag {
+    Red,
+    Green,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Config {
+    tags: BTreeSet<String>,
+    ids: HashSet<u32>,
+}
+
+#[test]
+fn test_set() {
+    assert_eq!(
+        from_str(
+            "#![enable(set_literals)]
+            (
+                tags: #{\"a\", \"b\"},
+                ids: #{1, 2, 3,},
+            )"
+        ),
+        Ok(Config {
+            tags: vec!["a".to_owned(), "b".to_owned()].into_iter().collect(),
+            ids: vec![1, 2, 3].into_iter().collect(),
+        })
+    );
+
+    assert_eq!(
+        from_str::<Vec<Tag>>("#![enable(set_literals)] #{Red, Green}"),
+        Ok(vec![Tag::Red, Tag::Green])
+    );
+    assert_eq!(
+        from_str::<Vec<u8>>("#![enable(set_literals)] #{}"),
+        Ok(vec![])
+    );
+
+    // lists still work
+    assert_eq!(
+        from_str::<BTreeSet<u8>>("#![enable(set_literals)] [1, 1]"),
+        Ok(vec![1].into_iter().collect())
+    );
+}
+
+#[test]
+fn test_duplicates() {
+    assert_eq!(
+        from_str::<HashSet<u32>>("#![enable(set_literals)] #{1, 2, 1}"),
+        Err(Error {
+            code: ErrorCode::DuplicateSetElement,
+            position: Position {
+                line: 1,
+                col: 34,
+                file: None
+            },
+        })
+    );
+
+    // elements are compared by their values, regardless of layout and comments
+    assert_eq!(
+        from_str::<Vec<(u8, String)>>(
+            "#![enable(set_literals)] #{(1, \"a b\"), ( 1 , /* one */ \"a b\" )}"
+        )
+        .map_err(|e| e.code),
+        Err(ErrorCode::DuplicateSetElement)
+    );
+    assert!(from_str::<Vec<String>>("#![enable(set_literals)] #{\"a b\", \"ab\"}").is_ok());
+    assert!(from_str::<Vec<Tag>>("#![enable(set_literals)] #{Red, Green, Red}").is_err());
+    assert!(from_str::<Vec<Tag>>("#![enable(set_literals)] #{Red, Green}").is_ok());
+
+    for set in &[
+        "#![enable(set_literals)] #{1, 0x1}",
+        "#![enable(set_literals)] #{\"a\", \"\\u{61}\"}",
+        "#![enable(set_literals, anchors)] #{&a \"a\", *a}",
+    ] {
+        assert_eq!(
+            from_str::<Vec<Value>>(set).map_err(|e| e.code),
+            Err(ErrorCode::DuplicateSetElement)
+        );
+    }
+
+    // nested sets are compared without deserializing them once per level
+    let nested = format!(
+        "#![enable(set_literals)] {}1{}",
+        "#{".repeat(40),
+        "}".repeat(40)
+    );
+    assert!(from_str::<Value>(&nested).is_ok());
+    assert_eq!(
+        from_str::<Vec<Vec<Vec<u8>>>>("#![enable(set_literals)] #{#{#{1, 1}}}").map_err(|e| e.code),
+        Err(ErrorCode::DuplicateSetElement)
+    );
+    assert_eq!(
+        from_str::<Vec<Vec<u8>>>("#![enable(set_literals)] #{#{1}, #{0x1}}").map_err(|e| e.code),
+        Err(ErrorCode::DuplicateSetElement)
+    );
+
+    assert_eq!(
+        from_str::<Vec<u8>>("#![enable(set_literals)] #{1, 2]").map_err(|e| e.code),
+        Err(ErrorCode::ExpectedSetEnd)
+    );
+    assert!(from_str::<Vec<u8>>("#{1, 2}").is_err());
+}
+
+#[test]
+fn test_value() {
+    assert_eq!(
+        "#![enable(set_literals)] (#{1, 2}, [1, 1], #{})".parse(),
+        Ok(Value::Seq(vec![
+            Value::Set(vec![
+                Value::Number(Number::new(1.0)),
+                Value::Number(Number::new(2.0)),
+            ]),
+            Value::Seq(vec![
+                Value::Number(Number::new(1.0)),
+                Value::Number(Number::new(1.0)),
+            ]),
+            Value::Set(vec![]),
+        ]))
+    );
+
+    let value = Value::Seq(vec![
+        Value::Set(vec![Value::Char('a'), Value::Char('b')]),
+        Value::Seq(vec![Value::Char('a')]),
+    ]);
+
+    let pretty = PrettyConfig::new()
+        .with_extensions(Extensions::SET_LITERALS)
+        .with_max_width(80);
+    assert_eq!(
+        to_string_pretty(&value, pretty),
+        Ok("#![enable(set_literals)]\n[#{'a', 'b'}, ['a']]".to_owned())
+    );
+
+    // a `Value::Set` becomes a list without the extension
+    assert_eq!(
+        to_string_pretty(&value, PrettyConfig::new().with_max_width(80)),
+        Ok("[['a', 'b'], ['a']]".to_owned())
+    );
+}
+
+#[test]
+fn test_untagged() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(untagged)]
+    enum Ids {
+        One(u8),
+        Many(Vec<u8>),
+    }
+
+    assert_eq!(
+        from_str("#![enable(set_literals)] #{1, 2}"),
+        Ok(Ids::Many(vec![1, 2]))
+    );
+}
@@ -11,3 +11,48 @@ fn test_string() {
     let de: String = from_str("\"My string: ऄ\"").unwrap();
     assert_eq!(de, "My string: ऄ");
 }
+
+#[test]
+fn test_identifiers() {
+    use ron::{
+        error::{Error, ErrorCode, Position},
+        ser::to_string,
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    enum Größe {
+        Klein,
+        #[serde(rename = "Très_grand")]
+        TrèsGrand,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Kleidung {
+        größe: Größe,
+        名前: String,
+    }
+
+    let value = Kleidung {
+        größe: Größe::TrèsGrand,
+        名前: "Mantel".to_owned(),
+    };
+    let serial = to_string(&value).unwrap();
+
+    assert_eq!(serial, "(größe:Très_grand,名前:\"Mantel\",)");
+    assert_eq!(from_str(&serial), Ok(value));
+    assert_eq!(from_str("Klein"), Ok(Größe::Klein));
+
+    // Columns count chars, not bytes
+    assert_eq!(
+        from_str::<Kleidung>("(größe: Klein, 名前: 名前)"),
+        Err(Error {
+            code: ErrorCode::ExpectedString,
+            position: Position {
+                line: 1,
+                col: 20,
+                file: None
+            },
+        })
+    );
+}
@@ -76,7 +76,11 @@ fn unit() {
         "".parse::<Value>(),
         Err(Error {
             code: ErrorCode::Eof,
-            position: Position { col: 1, line: 1 }
+            position: Position {
+                col: 1,
+                line: 1,
+                file: None
+            }
         })
     );
 }